@@ -10,7 +10,8 @@ use crate::{
     consts::TYPE_PARAMETERS_START,
     env::Env,
     library::{
-        Class, Concurrency, Function, Fundamental, Nullable, ParameterDirection, Type, TypeId,
+        Class, Concurrency, Function, Fundamental, Nullable, ParameterDirection, ParameterScope,
+        Type, TypeId,
     },
     traits::IntoString,
 };
@@ -35,7 +36,8 @@ impl BoundType {
 pub struct Bound {
     pub bound_type: BoundType,
     pub parameter_name: String,
-    pub alias: char,
+    /// `None` if the bound is written as `impl Trait` in argument position
+    pub alias: Option<char>,
     pub type_str: String,
     pub info_for_next_type: bool,
     pub callback_modified: bool,
@@ -80,11 +82,18 @@ impl Bounds {
         r#async: bool,
         concurrency: Concurrency,
         configured_functions: &[&config::functions::Function],
+        error_type: Option<&str>,
+        concrete_cancellable: bool,
     ) -> (Option<String>, Option<CallbackInfo>) {
         let type_name = bounds_rust_type(env, par.typ);
         if (r#async && async_param_to_remove(&par.name)) || type_name.is_err() {
             return (None, None);
         }
+        // Taken as `Option<&Cancellable>` so that `None` doesn't need a type annotation,
+        // hence there is no bound and nothing to upcast
+        if concrete_cancellable && par.typ.full_name(&env.library) == "Gio.Cancellable" {
+            return (Some(String::new()), None);
+        }
         let mut type_string = type_name.into_string();
         let mut callback_info = None;
         let mut ret = None;
@@ -93,7 +102,10 @@ impl Bounds {
         if !par.instance_parameter && par.direction != ParameterDirection::Out {
             if let Some(bound_type) = Bounds::type_for(env, par.typ, par.nullable) {
                 ret = Some(Bounds::get_to_glib_extra(&bound_type));
-                if r#async && (par.name == "callback" || par.name.ends_with("_callback")) {
+                if r#async
+                    && (par.name == "callback" || par.name.ends_with("_callback"))
+                    && !matches!(par.scope, ParameterScope::Call | ParameterScope::Notified)
+                {
                     let func_name = func.c_identifier.as_ref().unwrap();
                     let finish_func_name = finish_function_name(func_name);
                     if let Some(function) = find_function(env, &finish_func_name) {
@@ -111,7 +123,8 @@ impl Bounds {
                             );
                         }
                         let parameters = format_out_parameters(&out_parameters);
-                        let error_type = find_error_type(env, function);
+                        let error_type = error_type
+                            .map_or_else(|| find_error_type(env, function), ToOwned::to_owned);
                         type_string = format!(
                             "FnOnce(Result<{}, {}>) + Send + 'static",
                             parameters, error_type
@@ -139,9 +152,16 @@ impl Bounds {
                         });
                     }
                 }
+                // The type of callbacks is needed by their trampolines
+                let impl_trait = env.config.impl_trait_args
+                    && matches!(bound_type, BoundType::IsA(_) | BoundType::AsRef(_));
                 if (!need_is_into_check || !*par.nullable)
                     && par.c_type != "GDestroyNotify"
-                    && !self.add_parameter(&par.name, &type_string, bound_type, r#async)
+                    && !if impl_trait {
+                        self.add_impl_trait_parameter(&par.name, &type_string, bound_type)
+                    } else {
+                        self.add_parameter(&par.name, &type_string, bound_type, r#async)
+                    }
                 {
                     panic!(
                         "Too many type constraints for {}",
@@ -200,7 +220,7 @@ impl Bounds {
                 self.used.push(Bound {
                     bound_type: BoundType::NoWrapper,
                     parameter_name: name.to_owned(),
-                    alias,
+                    alias: Some(alias),
                     type_str: type_str.to_string(),
                     info_for_next_type: false,
                     callback_modified: false,
@@ -216,7 +236,7 @@ impl Bounds {
             self.used.push(Bound {
                 bound_type,
                 parameter_name: name.to_owned(),
-                alias,
+                alias: Some(alias),
                 type_str: type_str.to_owned(),
                 info_for_next_type: false,
                 callback_modified: false,
@@ -227,19 +247,43 @@ impl Bounds {
         }
     }
 
-    pub fn get_parameter_alias_info(&self, name: &str) -> Option<(char, BoundType)> {
-        self.used
-            .iter()
-            .find(move |n| {
-                if n.parameter_name == name {
-                    !n.info_for_next_type
-                } else {
-                    false
-                }
-            })
+    /// Adds a bound written as `impl Trait` in the parameter's type, for types which
+    /// aren't named in the function's body.
+    pub fn add_impl_trait_parameter(
+        &mut self,
+        name: &str,
+        type_str: &str,
+        bound_type: BoundType,
+    ) -> bool {
+        if self.used.iter().any(|n| n.parameter_name == name) {
+            return false;
+        }
+        self.used.push(Bound {
+            bound_type,
+            parameter_name: name.to_owned(),
+            alias: None,
+            type_str: type_str.to_owned(),
+            info_for_next_type: false,
+            callback_modified: false,
+        });
+        true
+    }
+
+    pub fn get_parameter_alias_info(&self, name: &str) -> Option<(Option<char>, BoundType)> {
+        self.get_parameter_bound(name)
             .map(|t| (t.alias, t.bound_type.clone()))
     }
 
+    pub fn get_parameter_bound(&self, name: &str) -> Option<&Bound> {
+        self.used.iter().find(move |n| {
+            if n.parameter_name == name {
+                !n.info_for_next_type
+            } else {
+                false
+            }
+        })
+    }
+
     pub fn get_base_alias(&self, alias: char) -> Option<char> {
         if alias == TYPE_PARAMETERS_START {
             return None;
@@ -247,14 +291,8 @@ impl Bounds {
         let prev_alias = ((alias as u8) - 1) as char;
         self.used
             .iter()
-            .find(move |n| n.alias == prev_alias)
-            .and_then(|b| {
-                if b.info_for_next_type {
-                    Some(b.alias)
-                } else {
-                    None
-                }
-            })
+            .find(move |n| n.alias == Some(prev_alias))
+            .and_then(|b| if b.info_for_next_type { b.alias } else { None })
     }
 
     pub fn update_imports(&self, imports: &mut Imports) {
@@ -369,9 +407,27 @@ mod tests {
         bounds.add_parameter("b", "", typ.clone(), false);
         assert_eq!(
             bounds.get_parameter_alias_info("a"),
-            Some(('P', typ.clone()))
+            Some((Some('P'), typ.clone()))
+        );
+        assert_eq!(
+            bounds.get_parameter_alias_info("b"),
+            Some((Some('Q'), typ.clone()))
         );
-        assert_eq!(bounds.get_parameter_alias_info("b"), Some(('Q', typ)));
         assert_eq!(bounds.get_parameter_alias_info("c"), None);
     }
+
+    #[test]
+    fn impl_trait_parameter() {
+        let mut bounds: Bounds = Default::default();
+        let typ = BoundType::IsA(None);
+        assert!(bounds.add_impl_trait_parameter("a", "", typ.clone()));
+        assert!(!bounds.add_impl_trait_parameter("a", "", typ.clone()));
+        bounds.add_parameter("b", "", typ.clone(), false);
+        assert_eq!(
+            bounds.get_parameter_alias_info("a"),
+            Some((None, typ.clone()))
+        );
+        // No alias is taken by `impl Trait` bounds
+        assert_eq!(bounds.get_parameter_alias_info("b"), Some((Some('P'), typ)));
+    }
 }
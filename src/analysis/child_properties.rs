@@ -104,7 +104,11 @@ fn analyze_property(
             // only keep the "inner" part to make the string computation easier. So
             // `<T: X>` becomes `T: X`.
             bounds_str.push_str(&s_bounds[1..s_bounds.len() - 1]);
-            format!("{}: {}", prop_name, bounds.iter().last().unwrap().alias)
+            format!(
+                "{}: {}",
+                prop_name,
+                bounds.iter().last().unwrap().alias.unwrap()
+            )
         } else {
             format!(
                 "{}: {}",
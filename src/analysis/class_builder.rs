@@ -129,5 +129,7 @@ fn analyze_property(
         bounds,
         version: prop_version,
         deprecated_version: prop.deprecated_version,
+        deprecated_feature_version: env
+            .deprecated_feature_version(prop.deprecated_version, Some(prop.typ)),
     })
 }
@@ -48,7 +48,8 @@ pub fn analyze<F: Borrow<library::Constant>>(
         let cfg_condition = configured_constants
             .iter()
             .filter_map(|c| c.cfg_condition.clone())
-            .next();
+            .next()
+            .or_else(|| env.config.cfg_condition_by_prefix(&constant.c_identifier));
 
         let name = nameutil::mangle_keywords(&*constant.name).into_owned();
 
@@ -64,6 +64,7 @@ impl ConversionType {
                 Unsupported => ConversionType::Unknown,
             },
             Alias(ref alias) if alias.c_identifier == "GQuark" => ConversionType::Scalar,
+            Alias(_) if env.is_newtype_alias(type_id) => ConversionType::Scalar,
             Alias(ref alias) => ConversionType::of(env, alias.typ),
             Bitfield(_) => ConversionType::Scalar,
             Record(_) => ConversionType::Pointer,
@@ -76,6 +77,7 @@ impl ConversionType {
             List(_) => ConversionType::Pointer,
             SList(_) => ConversionType::Pointer,
             PtrArray(_) => ConversionType::Pointer,
+            HashTable(..) => ConversionType::Pointer,
             Function(super::library::Function { ref name, .. }) if name == "AsyncReadyCallback" => {
                 ConversionType::Direct
             }
@@ -87,3 +89,17 @@ impl ConversionType {
         }
     }
 }
+
+/// Whether the type is a fixed-size array of numbers, which is copied by value.
+///
+/// Such arrays are still `ConversionType::Pointer`, only the function parameters
+/// and return values check for them to pass and copy them as `[T; N]`.
+pub fn is_direct_fixed_array(env: &env::Env, type_id: TypeId) -> bool {
+    match *env.library.type_(type_id) {
+        Type::FixedArray(inner_tid, ..) => {
+            matches!(*env.library.type_(inner_tid), Type::Fundamental(..))
+                && ConversionType::of(env, inner_tid) == ConversionType::Direct
+        }
+        _ => false,
+    }
+}
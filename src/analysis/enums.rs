@@ -10,6 +10,8 @@ pub struct Info {
     pub name: String,
     pub functions: Vec<functions::Info>,
     pub specials: special_functions::Infos,
+    /// Whether to generate the tables of the names and nicks of the members and `FromStr`
+    pub generate_tables: bool,
 }
 
 impl Info {
@@ -107,16 +109,26 @@ pub fn new(env: &Env, obj: &GObject, imports: &mut Imports) -> Option<Info> {
         }
     }
 
-    let specials = special_functions::extract(&mut functions, type_, obj);
+    let specials = special_functions::extract(env, &mut functions, enumeration_tid, obj);
 
     special_functions::analyze_imports(&specials, imports);
 
+    // The tables are skipped when a function has the name of one of theirs
+    let generate_tables = obj.generate_from_str
+        && !functions
+            .iter()
+            .any(|f| ["name", "nick", "from_name", "from_nick"].contains(&f.codegen_name()));
+    if generate_tables && !specials.has_trait(special_functions::Type::FromStr) {
+        imports.add("std::str");
+    }
+
     let info = Info {
         full_name: obj.name.clone(),
         type_id: enumeration_tid,
         name: name.to_owned(),
         functions,
         specials,
+        generate_tables,
     };
 
     Some(info)
@@ -0,0 +1,139 @@
+use super::{
+    conversion_type::ConversionType,
+    functions,
+    imports::Imports,
+    record_type::is_direct_field,
+    rust_type::{rust_type_nullable, used_rust_type},
+};
+use crate::{
+    config::{gobjects::GObject, matchable::Matchable},
+    env::Env,
+    library::{self, Nullable},
+    nameutil,
+    version::Version,
+};
+
+/// Accessors generated for a public field of a record or union.
+#[derive(Debug)]
+pub struct Info {
+    /// C name of the field
+    pub name: String,
+    pub typ: library::TypeId,
+    /// Rust type returned by the getter and taken by the setter
+    pub rust_type: String,
+    pub conversion_type: ConversionType,
+    /// Struct stored by value in the field, read with `from_glib_none` from its address
+    pub embedded: bool,
+    /// Tag values for which this union field is valid, resolved to enum variants by the union
+    /// analysis
+    pub tag_values: Vec<String>,
+    pub getter: Option<String>,
+    pub setter: Option<String>,
+    pub version: Option<Version>,
+}
+
+/// Analyzes the public fields of a record or union enabled with `generate_fields` or listed in
+/// `[[object.field]]`, along with the tag of a union. Fields passed by value get a getter and,
+/// when `is_mutable`, a setter. Pointer fields and embedded structs only get a getter returning
+/// a copy of the value.
+pub fn analyze(
+    env: &Env,
+    fields: &[library::Field],
+    obj: &GObject,
+    functions: &[functions::Info],
+    is_mutable: bool,
+    imports: &mut Imports,
+) -> Vec<Info> {
+    let has_method = |name: &str| {
+        functions
+            .iter()
+            .any(|f| f.status.need_generate() && f.codegen_name() == name)
+    };
+
+    let mut infos = Vec::new();
+    for field in fields {
+        if field.private || field.bits.is_some() {
+            continue;
+        }
+        let configured_fields = obj.fields.matched(&field.name);
+        if configured_fields.iter().any(|f| !f.status.need_generate()) {
+            continue;
+        }
+        // Accessors are opt-in, for all the fields or the configured ones
+        let is_tag = obj
+            .union_tag
+            .as_deref()
+            .filter(|tag| nameutil::mangle_keywords(*tag) == field.name)
+            .is_some();
+        if !obj.generate_fields && configured_fields.is_empty() && !is_tag {
+            continue;
+        }
+
+        let by_value = is_direct_field(env, field);
+        let embedded = !by_value && is_embedded_field(env, field);
+        if !by_value && !embedded && !is_pointer_field(env, field) {
+            continue;
+        }
+        let nullable = !by_value && !embedded;
+        let rust_type = match rust_type_nullable(env, field.typ, Nullable(nullable)) {
+            Ok(rust_type) => rust_type,
+            Err(_) => continue,
+        };
+        if let Ok(s) = used_rust_type(env, field.typ, false) {
+            imports.add_used_type(&s);
+        }
+
+        let name = configured_fields
+            .iter()
+            .find_map(|f| f.rename.clone())
+            .unwrap_or_else(|| nameutil::mangle_keywords(&*field.name).into_owned());
+        let getter = Some(name.clone()).filter(|n| !has_method(n));
+        let setter =
+            Some(format!("set_{}", name)).filter(|n| by_value && is_mutable && !has_method(n));
+        if getter.is_none() && setter.is_none() {
+            continue;
+        }
+
+        infos.push(Info {
+            name: field.name.clone(),
+            typ: field.typ,
+            rust_type,
+            conversion_type: ConversionType::of(env, field.typ),
+            embedded,
+            tag_values: configured_fields
+                .iter()
+                .flat_map(|f| f.tag_values.iter().cloned())
+                .collect(),
+            getter,
+            setter,
+            version: configured_fields.iter().filter_map(|f| f.version).max(),
+        });
+    }
+
+    infos
+}
+
+/// Whether the field is a pointer to a type that can be copied with `from_glib_none`.
+fn is_pointer_field(env: &Env, field: &library::Field) -> bool {
+    use crate::library::{Fundamental, Type::*};
+
+    if !matches!(field.c_type, Some(ref c_type) if c_type.contains('*')) {
+        return false;
+    }
+    match *env.library.type_(field.typ) {
+        Fundamental(Fundamental::Utf8)
+        | Fundamental(Fundamental::Filename)
+        | Fundamental(Fundamental::OsString) => true,
+        Record(ref record) => !record.disguised,
+        Class(..) | Interface(..) => true,
+        _ => false,
+    }
+}
+
+/// Whether the field is a struct stored by value.
+fn is_embedded_field(env: &Env, field: &library::Field) -> bool {
+    if !matches!(field.c_type, Some(ref c_type) if !c_type.contains('*')) {
+        return false;
+    }
+    matches!(*env.library.type_(field.typ), library::Type::Record(ref record) if !record.disguised)
+}
@@ -1,6 +1,10 @@
 use super::{
-    conversion_type::ConversionType, out_parameters::can_as_return,
-    override_string_type::override_string_type_parameter, ref_mode::RefMode, rust_type::rust_type,
+    conversion_type::{is_direct_fixed_array, ConversionType},
+    functions::is_carray_with_direct_elements,
+    out_parameters::can_as_return,
+    override_string_type::override_string_type_parameter,
+    ref_mode::RefMode,
+    rust_type::rust_type,
 };
 use crate::{
     config::{self, parameter_matchable::ParameterMatchable},
@@ -73,6 +77,8 @@ pub enum TransformationType {
     },
     IntoRaw(String),
     ToSome(String),
+    /// Caller-allocated buffer passed as `&mut [T]`
+    AsMutPtr(String),
 }
 
 impl TransformationType {
@@ -85,7 +91,8 @@ impl TransformationType {
             | ToGlibBorrow
             | ToGlibUnknown { .. }
             | ToSome(_)
-            | IntoRaw(_))
+            | IntoRaw(_)
+            | AsMutPtr(_))
     }
 
     pub fn set_to_glib_extra(&mut self, to_glib_extra_: &str) {
@@ -156,6 +163,9 @@ pub fn analyze(
     in_trait: bool,
 ) -> Parameters {
     let mut parameters = Parameters::new(function_parameters.len());
+    let caller_allocated_buffers = configured_functions
+        .iter()
+        .any(|f| f.caller_allocated_buffers);
 
     // Map: length argument position => array name
     let array_lengths: HashMap<u32, String> = function_parameters
@@ -178,7 +188,16 @@ pub fn analyze(
         let ind_c = parameters.c_parameters.len();
         let mut ind_rust = Some(parameters.rust_parameters.len());
 
-        let mut add_rust_parameter = match par.direction {
+        // Filled by the function, but passed as a mutable slice whose length is used for the
+        // length parameter
+        let is_buffer = caller_allocated_buffers && is_caller_allocated_buffer(env, par, typ);
+        let direction = if is_buffer {
+            library::ParameterDirection::In
+        } else {
+            par.direction
+        };
+
+        let mut add_rust_parameter = match direction {
             library::ParameterDirection::In | library::ParameterDirection::InOut => true,
             library::ParameterDirection::Return => false,
             library::ParameterDirection::Out => !can_as_return(env, par) && !async_func,
@@ -220,8 +239,11 @@ pub fn analyze(
         }
 
         let immutable = configured_parameters.iter().any(|p| p.constant);
-        let ref_mode =
-            RefMode::without_unneeded_mut(env, par, immutable, in_trait && par.instance_parameter);
+        let ref_mode = if is_buffer {
+            RefMode::ByRefMut
+        } else {
+            RefMode::without_unneeded_mut(env, par, immutable, in_trait && par.instance_parameter)
+        };
 
         let nullable_override = configured_parameters
             .iter()
@@ -234,7 +256,7 @@ pub fn analyze(
             typ,
             c_type,
             instance_parameter: par.instance_parameter,
-            direction: par.direction,
+            direction,
             transfer,
             caller_allocates,
             nullable,
@@ -277,6 +299,14 @@ pub fn analyze(
             };
 
         let transformation_type = match ConversionType::of(env, typ) {
+            _ if is_buffer => TransformationType::AsMutPtr(name),
+            _ if is_direct_fixed_array(env, typ) => TransformationType::ToGlibDirect {
+                name: if *nullable {
+                    format!("{}.map_or(std::ptr::null(), |a| a as *const _) as _", name)
+                } else {
+                    format!("{} as *const _ as _", name)
+                },
+            },
             ConversionType::Direct => {
                 if par.c_type != "GLib.Pid" {
                     TransformationType::ToGlibDirect { name }
@@ -332,6 +362,14 @@ pub fn analyze(
     parameters
 }
 
+/// Whether the parameter is a caller-allocated array of numbers with a length parameter.
+pub fn is_caller_allocated_buffer(env: &Env, par: &library::Parameter, typ: TypeId) -> bool {
+    par.direction == library::ParameterDirection::Out
+        && par.caller_allocates
+        && par.array_length.is_some()
+        && is_carray_with_direct_elements(env, typ)
+}
+
 fn get_length_type(
     env: &Env,
     array_name: &str,
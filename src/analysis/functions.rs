@@ -1,16 +1,15 @@
 /*
  * TODO: better heuristic (https://bugzilla.gnome.org/show_bug.cgi?id=623635#c5)
- * TODO: ProgressCallback types (not specific to async).
  * TODO: add annotation for methods like g_file_replace_contents_bytes_async where the finish
  * method has a different prefix.
  */
 
 use crate::{
     analysis::{
-        bounds::{Bounds, CallbackInfo},
+        bounds::{BoundType, Bounds, CallbackInfo},
         function_parameters::{self, CParameter, Parameters, Transformation, TransformationType},
         imports::Imports,
-        is_gpointer,
+        is_gpointer, namespaces,
         out_parameters::{self, use_function_return_for_result},
         ref_mode::RefMode,
         return_value,
@@ -22,7 +21,7 @@ use crate::{
     config::{self, gobjects::GStatus},
     env::Env,
     library::{self, Function, FunctionKind, Nullable, Parameter, ParameterScope, Transfer, Type},
-    nameutil,
+    nameutil::{self, use_glib_type},
     traits::*,
     version::Version,
 };
@@ -55,6 +54,9 @@ pub struct AsyncTrampoline {
     pub bound_name: char,
     pub output_params: Vec<Parameter>,
     pub ffi_ret: Option<Parameter>,
+    /// Callback reporting the progress of the operation, called with the user data of the
+    /// async callback
+    pub progress: Option<Trampoline>,
 }
 
 #[derive(Clone, Debug)]
@@ -64,6 +66,26 @@ pub struct AsyncFuture {
     pub success_parameters: String,
     pub error_parameters: String,
     pub assertion: SafetyAssertionMode,
+    pub stream: Option<AsyncStream>,
+}
+
+/// Stream of the results of the `_future` function called until the end condition.
+#[derive(Clone, Debug)]
+pub struct AsyncStream {
+    pub name: String,
+    pub item: String,
+    /// Whether the results are converted into `item`
+    pub convert: bool,
+    pub end: StreamEnd,
+}
+
+/// Result of the `_future` function ending a stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamEnd {
+    /// The function returned `None`
+    None,
+    /// The function returned an empty result, configured with `end = "empty"`
+    Empty,
 }
 
 #[derive(Debug)]
@@ -81,6 +103,9 @@ pub struct Info {
     pub outs: out_parameters::Info,
     pub version: Option<Version>,
     pub deprecated_version: Option<Version>,
+    /// Version from which the function is put behind the `deprecated` feature, the earliest of
+    /// its own deprecation and of the gated types in its signature
+    pub deprecated_feature_version: Option<Version>,
     pub not_version: Option<Version>,
     pub cfg_condition: Option<String>,
     pub assertion: SafetyAssertionMode,
@@ -91,6 +116,12 @@ pub struct Info {
     pub destroys: Vec<Trampoline>,
     pub remove_params: Vec<usize>,
     pub async_future: Option<AsyncFuture>,
+    /// Rust type of the error domain enum the function is known to fail with
+    pub error_domain: Option<String>,
+    /// C name of the varargs function generated from this vector twin
+    pub varargs: Option<String>,
+    /// `GClosure` parameters taken as Rust closures, called through a trampoline
+    pub closures: Vec<Trampoline>,
 }
 
 impl Info {
@@ -98,6 +129,15 @@ impl Info {
         self.new_name.as_ref().unwrap_or(&self.name)
     }
 
+    /// Returns the error type of a throwing function, `TypedError<Domain>` if it has an error
+    /// domain configured.
+    pub fn error_type(&self, env: &Env) -> String {
+        match self.error_domain {
+            Some(ref domain) => format!("TypedError<{}>", domain),
+            None => use_glib_type(env, "Error"),
+        }
+    }
+
     pub fn is_async_finish(&self, env: &Env) -> bool {
         let has_async_result = self
             .parameters
@@ -121,6 +161,21 @@ pub fn analyze<F: Borrow<library::Function>>(
 ) -> Vec<Info> {
     let mut funcs = Vec::new();
 
+    // Varargs functions generated from their configured twin taking a vector
+    let varargs_twins = functions
+        .iter()
+        .map(Borrow::borrow)
+        .filter(|func| !env.is_totally_deprecated(func.deprecated_version))
+        .filter_map(|func| {
+            let configured_functions = obj.functions.matched(&func.name);
+            if configured_functions.iter().any(|f| f.status.ignored()) {
+                return None;
+            }
+            let twin = find_varargs_twin(env, func, functions, &configured_functions)?;
+            Some((func.name.as_str(), twin))
+        })
+        .collect::<HashMap<_, _>>();
+
     'func: for func in functions {
         let func = func.borrow();
         let configured_functions = obj.functions.matched(&func.name);
@@ -157,18 +212,41 @@ pub fn analyze<F: Borrow<library::Function>>(
             signatures.insert(name.clone(), signature_params);
         }
 
-        let mut info = analyze_function(
-            env,
-            obj,
-            name,
-            status,
-            func,
-            type_tid,
-            in_trait,
-            is_boxed,
-            &configured_functions,
-            imports,
-        );
+        let mut info = match varargs_twins.get(func.name.as_str()) {
+            // Generated from the twin taking a vector, under the name of the varargs function
+            Some(twin) => {
+                let twin_configured_functions = obj.functions.matched(&twin.name);
+                let mut info = analyze_function(
+                    env,
+                    obj,
+                    name.clone(),
+                    status,
+                    twin,
+                    type_tid,
+                    in_trait,
+                    is_boxed,
+                    &twin_configured_functions,
+                    imports,
+                );
+                let (name, new_name) = generated_names(name, func.kind, &configured_functions);
+                info.name = name;
+                info.new_name = new_name;
+                info.varargs = func.c_identifier.clone();
+                info
+            }
+            None => analyze_function(
+                env,
+                obj,
+                name,
+                status,
+                func,
+                type_tid,
+                in_trait,
+                is_boxed,
+                &configured_functions,
+                imports,
+            ),
+        };
         info.not_version = not_version;
         funcs.push(info);
     }
@@ -176,6 +254,59 @@ pub fn analyze<F: Borrow<library::Function>>(
     funcs
 }
 
+/// Returns the name of a function, without the `new_` prefix of `new_from_*` and
+/// `new_with_*` constructors, and the name it is renamed to by the configuration.
+fn generated_names(
+    name: String,
+    kind: FunctionKind,
+    configured_functions: &[&config::functions::Function],
+) -> (String, Option<String>) {
+    let new_name = configured_functions.iter().find_map(|f| f.rename.clone());
+    if new_name.is_none()
+        && kind == FunctionKind::Constructor
+        && (name.starts_with("new_from") || name.starts_with("new_with"))
+    {
+        return (name[4..].to_string(), None);
+    }
+    (name, new_name)
+}
+
+fn is_varargs(env: &Env, func: &library::Function) -> bool {
+    func.parameters.iter().any(|par| {
+        matches!(
+            *env.library.type_(par.typ),
+            Type::Fundamental(library::Fundamental::VarArgs)
+        )
+    })
+}
+
+/// Finds the function taking a vector instead of the varargs of `func`, configured with
+/// `varargs_twin`.
+fn find_varargs_twin<'a, F: Borrow<library::Function>>(
+    env: &Env,
+    func: &library::Function,
+    functions: &'a [F],
+    configured_functions: &[&config::functions::Function],
+) -> Option<&'a library::Function> {
+    if !is_varargs(env, func) {
+        return None;
+    }
+    let twin_name = configured_functions
+        .iter()
+        .find_map(|f| f.varargs_twin.as_ref())?;
+    let twin = functions
+        .iter()
+        .map(Borrow::borrow)
+        .find(|f| &f.name == twin_name && f.kind == func.kind && !is_varargs(env, f));
+    if twin.is_none() {
+        warn!(
+            "Varargs twin {} of function {} not found",
+            twin_name, func.name
+        );
+    }
+    twin
+}
+
 fn fixup_gpointer_parameter(
     env: &Env,
     type_tid: library::TypeId,
@@ -275,6 +406,7 @@ fn analyze_callbacks(
     commented: &mut bool,
     concurrency: library::Concurrency,
     type_tid: library::TypeId,
+    concrete_cancellable: bool,
 ) {
     let mut to_replace = Vec::new();
     let mut to_remove = Vec::new();
@@ -319,6 +451,8 @@ fn analyze_callbacks(
                     false,
                     concurrency,
                     configured_functions,
+                    None,
+                    concrete_cancellable,
                 );
                 if let Some(to_glib_extra) = to_glib_extra {
                     if par.c_type != "GDestroyNotify" {
@@ -479,7 +613,7 @@ fn analyze_callbacks(
 fn analyze_function(
     env: &Env,
     obj: &config::gobjects::GObject,
-    mut name: String,
+    name: String,
     status: GStatus,
     func: &library::Function,
     type_tid: library::TypeId,
@@ -504,6 +638,7 @@ fn analyze_function(
     };
 
     let mut commented = false;
+    let concrete_cancellable = env.config.concrete_cancellable(&obj.name);
     let mut bounds: Bounds = Default::default();
     let mut to_glib_extras = HashMap::<usize, String>::new();
     let mut used_types: Vec<String> = Vec::with_capacity(4);
@@ -535,24 +670,40 @@ fn analyze_function(
         .min()
         .or(func.version);
 
-    let new_name = configured_functions
-        .iter()
-        .filter_map(|f| f.rename.clone())
-        .next();
-    if new_name.is_none() {
-        if let library::FunctionKind::Constructor = func.kind {
-            if name.starts_with("new_from") || name.starts_with("new_with") {
-                name = name[4..].to_string();
-            }
-        }
-    };
+    let (name, new_name) = generated_names(name, func.kind, configured_functions);
 
     let version = env.config.filter_version(version);
     let deprecated_version = func.deprecated_version;
+    let deprecated_feature_version = env.deprecated_feature_version(
+        deprecated_version,
+        func.parameters
+            .iter()
+            .filter(|par| !par.instance_parameter)
+            .chain(std::iter::once(&func.ret))
+            .map(|par| par.typ),
+    );
     let cfg_condition = configured_functions
         .iter()
         .filter_map(|f| f.cfg_condition.clone())
-        .next();
+        .next()
+        .or_else(|| {
+            let cfg_condition = env
+                .config
+                .cfg_condition_by_prefix(func.c_identifier.as_ref()?)?;
+            // Don't repeat the condition already applied to the whole type
+            let type_cfg_condition = env
+                .library
+                .type_(type_tid)
+                .get_glib_name()
+                .and_then(|name| env.config.cfg_condition_by_prefix(name));
+            if obj.cfg_condition.as_ref() == Some(&cfg_condition)
+                || type_cfg_condition.as_ref() == Some(&cfg_condition)
+            {
+                None
+            } else {
+                Some(cfg_condition)
+            }
+        });
     let doc_hidden = configured_functions.iter().any(|f| f.doc_hidden);
     let disable_length_detect = configured_functions.iter().any(|f| f.disable_length_detect);
     let no_future = configured_functions.iter().any(|f| f.no_future);
@@ -563,6 +714,22 @@ fn analyze_function(
 
     let imports = &mut imports.with_defaults(version, &cfg_condition);
 
+    let error_domain = analyze_error_domain(
+        env,
+        obj,
+        func,
+        type_tid,
+        r#async,
+        configured_functions,
+        &mut used_types,
+    );
+    if error_domain.is_some() {
+        imports.add("crate::TypedError");
+    }
+    let error_type = error_domain
+        .as_ref()
+        .map(|domain| format!("TypedError<{}>", domain));
+
     let ret = return_value::analyze(
         env,
         obj,
@@ -594,6 +761,31 @@ fn analyze_function(
         }
     }
 
+    if status.need_generate() && env.config.work_mode.is_normal() {
+        if let Some(par) = ret
+            .parameter
+            .iter()
+            .chain(&func.parameters)
+            .find(|par| is_unsupported_hash_table(env, par.typ))
+        {
+            if let Type::HashTable(key_tid, value_tid) = *env.library.type_(par.typ) {
+                warn!(
+                    "Function \"{}\" skipped, {} is a hash table of {} to {}, only hash tables \
+                     of strings are supported",
+                    func.name,
+                    if par.direction == library::ParameterDirection::Return {
+                        "its return value"
+                    } else {
+                        &par.name
+                    },
+                    env.library.type_(key_tid).get_name(),
+                    env.library.type_(value_tid).get_name()
+                );
+            }
+            commented = true;
+        }
+    }
+
     fixup_special_functions(env, name.as_str(), type_tid, is_boxed, &mut parameters);
 
     // Key: destroy callback index
@@ -603,6 +795,16 @@ fn analyze_function(
 
     if status.need_generate() {
         if !has_callback_parameter {
+            let mut progress = None;
+            if r#async {
+                // Progress callbacks are still called after the function returned, until the
+                // operation completes
+                for par in &mut parameters.c_parameters {
+                    if is_progress_callback(env, par) {
+                        par.scope = ParameterScope::Notified;
+                    }
+                }
+            }
             for (pos, par) in parameters.c_parameters.iter().enumerate() {
                 // FIXME: It'd be better if we assumed that user data wasn't gpointer all the time so
                 //        we could handle it more generically.
@@ -626,23 +828,47 @@ fn analyze_function(
                     r#async,
                     library::Concurrency::None,
                     configured_functions,
+                    error_type.as_deref(),
+                    concrete_cancellable,
                 );
                 if let Some(to_glib_extra) = to_glib_extra {
                     to_glib_extras.insert(pos, to_glib_extra);
                 }
 
-                analyze_async(
-                    env,
-                    func,
-                    type_tid,
-                    callback_info,
-                    &mut commented,
-                    &mut trampoline,
-                    no_future,
-                    &mut async_future,
-                    configured_functions,
-                    &parameters,
-                );
+                if r#async && is_progress_callback(env, par) {
+                    if progress.is_some() {
+                        warn_main!(
+                            type_tid,
+                            "{}: Only one progress callback is supported in async functions",
+                            func.name
+                        );
+                        commented = true;
+                    } else {
+                        progress = analyze_progress_callback(
+                            env,
+                            func,
+                            type_tid,
+                            par,
+                            &parameters,
+                            callback_info,
+                            &mut commented,
+                            imports,
+                        );
+                    }
+                } else {
+                    analyze_async(
+                        env,
+                        func,
+                        type_tid,
+                        callback_info,
+                        &mut commented,
+                        &mut trampoline,
+                        no_future,
+                        &mut async_future,
+                        configured_functions,
+                        &parameters,
+                    );
+                }
                 let type_error = !(r#async
                     && *env.library.type_(par.typ)
                         == Type::Fundamental(library::Fundamental::Pointer))
@@ -662,6 +888,11 @@ fn analyze_function(
             if r#async && trampoline.is_none() {
                 commented = true;
             }
+            if let (Some(progress), Some(trampoline)) = (progress, trampoline.as_mut()) {
+                // The progress callback is stored with the async callback, freed once the
+                // operation completes
+                trampoline.progress = Some(progress);
+            }
         } else {
             analyze_callbacks(
                 env,
@@ -682,10 +913,30 @@ fn analyze_function(
                 &mut commented,
                 concurrency,
                 type_tid,
+                concrete_cancellable,
             );
         }
     }
 
+    let closures = if status.need_generate() && !commented {
+        analyze_closures(
+            env,
+            func,
+            type_tid,
+            &mut parameters,
+            configured_functions,
+            &mut bounds,
+            &mut used_types,
+            concurrency,
+        )
+    } else {
+        Vec::new()
+    };
+    if !closures.is_empty() {
+        imports.add("std::boxed::Box as Box_");
+        imports.add("std::mem::transmute");
+    }
+
     for par in &parameters.rust_parameters {
         // Disallow fundamental arrays without length
         let is_len_for_par = |t: &&Transformation| {
@@ -798,6 +1049,7 @@ fn analyze_function(
         outs,
         version,
         deprecated_version,
+        deprecated_feature_version,
         not_version: None,
         cfg_condition,
         assertion,
@@ -808,7 +1060,193 @@ fn analyze_function(
         callbacks,
         destroys,
         remove_params: cross_user_data_check.values().cloned().collect::<Vec<_>>(),
+        error_domain,
+        varargs: None,
+        closures,
+    }
+}
+
+/// Finds the `GClosure` parameters with a configured `closure_signature`, they are taken as a
+/// Rust closure with the signature of the given callback type.
+fn analyze_closures(
+    env: &Env,
+    func: &library::Function,
+    type_tid: library::TypeId,
+    parameters: &mut Parameters,
+    configured_functions: &[&config::functions::Function],
+    bounds: &mut Bounds,
+    used_types: &mut Vec<String>,
+    concurrency: library::Concurrency,
+) -> Vec<Trampoline> {
+    // Only closures of thread safe types can be invoked from any thread
+    let concurrency = if concurrency == library::Concurrency::SendSync {
+        concurrency
+    } else {
+        library::Concurrency::None
+    };
+    let mut closures = Vec::new();
+    for (ind_c, par) in parameters.c_parameters.iter().enumerate() {
+        let signature = match configured_functions
+            .matched_parameters(&par.name)
+            .iter()
+            .find_map(|p| p.closure_signature.as_ref())
+        {
+            Some(signature) => signature,
+            None => continue,
+        };
+        if par.typ.full_name(&env.library) != "GObject.Closure" {
+            warn_main!(
+                type_tid,
+                "Ignoring closure_signature of {} parameter {}, it isn't a GClosure",
+                func.name,
+                par.name
+            );
+            continue;
+        }
+        let callback_tid = match env.library.find_type(namespaces::MAIN, signature) {
+            Some(tid) if env.library.type_(tid).is_function() => tid,
+            _ => {
+                warn_main!(
+                    type_tid,
+                    "Closure signature {} of {} isn't a callback type",
+                    signature,
+                    func.name
+                );
+                continue;
+            }
+        };
+        let callback: &Function = env.library.type_(callback_tid).maybe_ref().unwrap();
+        // The `GCClosure` passes its data as the last argument of the trampoline
+        let callback_parameters = match callback.parameters.split_last() {
+            Some((user_data, callback_parameters)) if is_gpointer(&user_data.c_type) => {
+                callback_parameters
+            }
+            _ => {
+                warn_main!(
+                    type_tid,
+                    "Closure signature {} of {} doesn't end with its user data",
+                    signature,
+                    func.name
+                );
+                continue;
+            }
+        };
+        let closure_type =
+            rust_type_with_scope(env, callback_tid, ParameterScope::Notified, concurrency)
+                .ok()
+                .filter(|_| {
+                    callback_parameters
+                        .iter()
+                        .all(|p| crate::analysis::trampolines::type_error(env, p).is_none())
+                });
+        let closure_type = match closure_type {
+            Some(closure_type) => closure_type,
+            None => {
+                warn_main!(
+                    type_tid,
+                    "Closure signature {} of {} can't be generated",
+                    signature,
+                    func.name
+                );
+                continue;
+            }
+        };
+        for p in callback_parameters.iter().chain(Some(&callback.ret)) {
+            if let Ok(s) = used_rust_type(env, p.typ, false) {
+                used_types.push(s);
+            }
+        }
+        // Like the callbacks, the trampoline is generic over the closure's type
+        if !bounds.add_parameter(&par.name, &closure_type, BoundType::NoWrapper, false) {
+            panic!(
+                "Too many type constraints for {}",
+                func.c_identifier.as_ref().unwrap()
+            )
+        }
+        let bound_name = bounds
+            .get_parameter_bound(&par.name)
+            .and_then(|bound| bound.alias)
+            .unwrap();
+        // The closure is built locally, only borrow it for the call
+        for transformation in &mut parameters.transformations {
+            if transformation.ind_c != ind_c {
+                continue;
+            }
+            if let TransformationType::ToGlibPointer {
+                ref mut ref_mode, ..
+            } = transformation.transformation_type
+            {
+                *ref_mode = RefMode::ByRef;
+            }
+        }
+        closures.push(Trampoline {
+            name: par.name.clone(),
+            parameters: crate::analysis::trampoline_parameters::analyze(
+                env,
+                &callback.parameters,
+                callback_tid,
+                &[],
+            ),
+            ret: callback.ret.clone(),
+            bound_name: bound_name.to_string(),
+            bounds: Bounds::default(),
+            version: None,
+            inhibit: false,
+            concurrency,
+            is_notify: false,
+            scope: ParameterScope::Notified,
+            user_data_index: 0,
+            destroy_index: 0,
+            nullable: Nullable(false),
+            type_name: env.library.type_(type_tid).get_name(),
+        });
+    }
+    closures
+}
+
+/// Returns the Rust type of the error domain configured for a throwing or async function.
+fn analyze_error_domain(
+    env: &Env,
+    obj: &config::gobjects::GObject,
+    func: &library::Function,
+    type_tid: library::TypeId,
+    r#async: bool,
+    configured_functions: &[&config::functions::Function],
+    used_types: &mut Vec<String>,
+) -> Option<String> {
+    if !func.throws && !r#async {
+        return None;
+    }
+    let name = configured_functions
+        .iter()
+        .find_map(|f| f.error_domain.as_ref())
+        .or(obj.error_domain.as_ref())?;
+    let domain_tid = match env.library.find_type(namespaces::MAIN, name) {
+        Some(tid) => tid,
+        None => {
+            warn_main!(type_tid, "Unknown error domain {} for {}", name, func.name);
+            return None;
+        }
+    };
+    match *env.library.type_(domain_tid) {
+        Type::Enumeration(library::Enumeration {
+            error_domain: Some(_),
+            ..
+        }) => (),
+        _ => {
+            warn_main!(
+                type_tid,
+                "{} is not an error domain enumeration, ignored for {}",
+                name,
+                func.name
+            );
+            return None;
+        }
     }
+    if let Ok(s) = used_rust_type(env, domain_tid, false) {
+        used_types.push(s);
+    }
+    Some(rust_type(env, domain_tid).into_string())
 }
 
 pub fn is_carray_with_direct_elements(env: &Env, typ: library::TypeId) -> bool {
@@ -907,14 +1345,25 @@ fn analyze_async(
             bound_name,
             output_params,
             ffi_ret,
+            progress: None,
         });
 
+        let stream = analyze_stream(
+            env,
+            func,
+            type_tid,
+            configured_functions,
+            parameters,
+            &success_parameters,
+            no_future,
+        );
         if !no_future {
             *async_future = Some(AsyncFuture {
                 is_method,
                 name: format!("{}_future", func.name),
                 success_parameters,
                 error_parameters,
+                stream,
                 assertion: match SafetyAssertionMode::of(env, is_method, &parameters) {
                     SafetyAssertionMode::None => SafetyAssertionMode::None,
                     // "_future" functions calls the "async" one which has the init check, so no
@@ -929,6 +1378,121 @@ fn analyze_async(
     }
 }
 
+/// Returns the configured stream of an async function, which needs its `_future` variant and
+/// parameters passed by value to call it repeatedly.
+fn analyze_stream(
+    env: &Env,
+    func: &library::Function,
+    type_tid: library::TypeId,
+    configured_functions: &[&config::functions::Function],
+    parameters: &Parameters,
+    success_parameters: &str,
+    no_future: bool,
+) -> Option<AsyncStream> {
+    let stream = configured_functions
+        .iter()
+        .find_map(|f| f.stream.as_ref())?;
+    if no_future {
+        warn_main!(
+            type_tid,
+            "{}: Streams need the future variant, ignored with no_future",
+            func.name
+        );
+        return None;
+    }
+    let by_reference = parameters.rust_parameters.iter().find(|par| {
+        let c_par = &parameters.c_parameters[par.ind_c];
+        !c_par.instance_parameter
+            && par.name != "cancellable"
+            && par.name != "callback"
+            && (c_par.ref_mode != RefMode::None
+                || *c_par.nullable
+                || env.library.type_(c_par.typ).is_function())
+    });
+    if let Some(par) = by_reference {
+        warn_main!(
+            type_tid,
+            "{}: Can't generate a stream, parameter {} isn't passed by value",
+            func.name,
+            par.name
+        );
+        return None;
+    }
+    // Nullable results end the stream on `None` and their items are the inner type
+    let (end, result) = match success_parameters
+        .strip_prefix("Option<")
+        .and_then(|inner| inner.strip_suffix('>'))
+    {
+        _ if stream.end_on_empty => (StreamEnd::Empty, success_parameters),
+        Some(inner) => (StreamEnd::None, inner),
+        None => {
+            warn_main!(
+                type_tid,
+                "{}: Can't generate a stream, its result isn't nullable and `end = \"empty\"` isn't set",
+                func.name
+            );
+            return None;
+        }
+    };
+    let name = func.name.strip_suffix("_async").unwrap_or(&func.name);
+    Some(AsyncStream {
+        name: format!("{}_stream", name),
+        item: stream.item.clone().unwrap_or_else(|| result.to_owned()),
+        convert: matches!(stream.item, Some(ref item) if item != result),
+        end,
+    })
+}
+
+/// Whether the parameter of an async function is a callback called during the operation,
+/// like the progress callback of `g_file_copy_async`.
+fn is_progress_callback(env: &Env, par: &CParameter) -> bool {
+    env.library.type_(par.typ).is_function()
+        && matches!(par.scope, ParameterScope::Call | ParameterScope::Notified)
+}
+
+fn analyze_progress_callback(
+    env: &Env,
+    func: &library::Function,
+    type_tid: library::TypeId,
+    par: &CParameter,
+    parameters: &Parameters,
+    callback_info: Option<CallbackInfo>,
+    commented: &mut bool,
+    imports: &mut Imports,
+) -> Option<Trampoline> {
+    let c_parameters = parameters
+        .c_parameters
+        .iter()
+        .enumerate()
+        .filter(|(_, par)| !par.instance_parameter)
+        .map(|(pos, par)| (par, pos))
+        .collect::<Vec<_>>();
+    let func_name = func.c_identifier.as_ref().unwrap_or(&func.name);
+    // Nullable callbacks are taken as boxed closures instead of a bound
+    let callback_info = if *par.nullable { None } else { callback_info };
+    let (callback, destroy_index) = analyze_callback(
+        func_name,
+        type_tid,
+        env,
+        par,
+        &callback_info,
+        commented,
+        imports,
+        &c_parameters,
+        env.library.type_(par.typ),
+    )?;
+    if destroy_index.is_some() {
+        warn_main!(
+            type_tid,
+            "{}: Progress callbacks with a destroy notify aren't supported",
+            func.name
+        );
+        *commented = true;
+        return None;
+    }
+    Some(callback)
+}
+
 fn analyze_callback(
     func_name: &str,
     type_tid: library::TypeId,
@@ -1161,6 +1725,85 @@ pub fn find_index_to_ignore(parameters: &[Parameter], ret: Option<&Parameter>) -
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::env::tests::env;
+
+    const VARARGS: &str = r#"
+    <function name="join" c:identifier="test_join" introspectable="0">
+      <return-value transfer-ownership="full"><type name="utf8" c:type="gchar*"/></return-value>
+      <parameters>
+        <parameter name="separator" transfer-ownership="none"><type name="utf8" c:type="const gchar*"/></parameter>
+        <parameter name="..." transfer-ownership="none"><varargs/></parameter>
+      </parameters>
+    </function>
+    <function name="joinv" c:identifier="test_joinv">
+      <return-value transfer-ownership="full"><type name="utf8" c:type="gchar*"/></return-value>
+      <parameters>
+        <parameter name="separator" transfer-ownership="none"><type name="utf8" c:type="const gchar*"/></parameter>
+        <parameter name="strings" transfer-ownership="none"><array c:type="gchar**"><type name="utf8"/></array></parameter>
+      </parameters>
+    </function>
+    <function name="concat" c:identifier="test_concat" introspectable="0">
+      <return-value transfer-ownership="full"><type name="utf8" c:type="gchar*"/></return-value>
+      <parameters>
+        <parameter name="..." transfer-ownership="none"><varargs/></parameter>
+      </parameters>
+    </function>
+    <function name="concat_v" c:identifier="test_concat_v">
+      <return-value transfer-ownership="full"><type name="utf8" c:type="gchar*"/></return-value>
+      <parameters>
+        <parameter name="strings" transfer-ownership="none"><array c:type="gchar**"><type name="utf8"/></array></parameter>
+      </parameters>
+    </function>
+    <function name="build" c:identifier="test_build" introspectable="0">
+      <return-value transfer-ownership="full"><type name="utf8" c:type="gchar*"/></return-value>
+      <parameters>
+        <parameter name="..." transfer-ownership="none"><varargs/></parameter>
+      </parameters>
+    </function>
+    <function name="build_from_array" c:identifier="test_build_from_array">
+      <return-value transfer-ownership="full"><type name="utf8" c:type="gchar*"/></return-value>
+      <parameters>
+        <parameter name="strings" transfer-ownership="none"><array c:type="gchar**"><type name="utf8"/></array></parameter>
+      </parameters>
+    </function>
+"#;
+    const VARARGS_CONFIG: &str = r#"
+[[object]]
+name = "Test.*"
+status = "generate"
+    [[object.function]]
+    name = "join"
+    varargs_twin = "joinv"
+    [[object.function]]
+    name = "build"
+    varargs_twin = "build_from_array"
+"#;
+
+    #[test]
+    fn varargs_twin_is_opt_in() {
+        let code = env(VARARGS, VARARGS_CONFIG).generate("functions.rs");
+        // `concat_v` isn't configured as the twin of `concat`
+        assert!(!code.contains("\npub fn concat("));
+        assert!(code.contains("pub fn concat_v(strings: &[&str]) -> Option<glib::GString> {"));
+    }
+
+    #[test]
+    fn configured_varargs_twin() {
+        let code = env(VARARGS, VARARGS_CONFIG).generate("functions.rs");
+        assert!(code.contains(
+            "#[doc(alias = \"test_join\")]\n#[doc(alias = \"test_joinv\")]\npub fn join(separator: &str, strings: &[&str]) -> Option<glib::GString> {"
+        ));
+        assert!(
+            code.contains("ffi::test_joinv(separator.to_glib_none().0, strings.to_glib_none().0)")
+        );
+        assert!(code.contains(
+            "#[doc(alias = \"test_build\")]\n#[doc(alias = \"test_build_from_array\")]\npub fn build(strings: &[&str]) -> Option<glib::GString> {"
+        ));
+        assert!(code.contains("ffi::test_build_from_array(strings.to_glib_none().0)"));
+        // The twins are still generated
+        assert!(code.contains("pub fn joinv(separator: &str, strings: &[&str])"));
+        assert!(code.contains("pub fn build_from_array(strings: &[&str])"));
+    }
 
     #[test]
     fn test_finish_function_name() {
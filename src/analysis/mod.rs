@@ -1,10 +1,11 @@
 use crate::{
     env::Env,
     library::{self, Type, TypeId},
+    nameutil,
 };
 use imports::Imports;
 use log::error;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 
 pub mod bounds;
 pub mod c_type;
@@ -15,6 +16,7 @@ pub mod constants;
 pub mod conversion_type;
 pub mod enums;
 pub mod ffi_type;
+pub mod fields;
 pub mod flags;
 pub mod function_parameters;
 pub mod functions;
@@ -40,12 +42,15 @@ pub mod symbols;
 pub mod trampoline_parameters;
 pub mod trampolines;
 pub mod types;
+pub mod union;
 
 #[derive(Debug, Default)]
 pub struct Analysis {
     pub objects: BTreeMap<String, object::Info>,
     pub records: BTreeMap<String, record::Info>,
+    pub unions: BTreeMap<String, union::Info>,
     pub global_functions: Option<info_base::InfoBase>,
+    pub global_functions_modules: Vec<info_base::InfoBase>,
     pub constants: Vec<constants::Info>,
     pub enumerations: Vec<enums::Info>,
     pub enum_imports: Imports,
@@ -159,38 +164,92 @@ fn analyze_global_functions(env: &mut Env) {
         _ => return,
     };
 
-    let functions: Vec<_> = ns
+    // Functions of the default `functions` module first, then one group per configured
+    // `[[functions_module]]` in the configuration order.
+    let type_modules = type_module_names(env);
+    let mut modules: Vec<(Option<&str>, Vec<&library::Function>)> = vec![(None, Vec::new())];
+    for module in &env.config.functions_modules {
+        if type_modules.contains(&module.name) {
+            error!(
+                "functions_module `{}` has the name of the module of a generated type, its functions are generated in `functions`",
+                module.name
+            );
+        } else if !modules
+            .iter()
+            .any(|(m, _)| *m == Some(module.name.as_str()))
+        {
+            modules.push((Some(module.name.as_str()), Vec::new()));
+        }
+    }
+    for func in ns
         .functions
         .iter()
         .filter(|f| f.kind == library::FunctionKind::Global)
-        .collect();
-    if functions.is_empty() {
-        return;
+    {
+        let module = env
+            .config
+            .functions_module(&func.name)
+            .filter(|m| !type_modules.contains(*m));
+        if let Some((_, functions)) = modules.iter_mut().find(|(m, _)| *m == module) {
+            functions.push(func);
+        }
     }
 
-    let mut imports = imports::Imports::new(&env.library);
-    imports.add("glib::translate::*");
+    let mut infos = Vec::new();
+    for (module, functions) in modules {
+        if functions.is_empty() {
+            continue;
+        }
+
+        let mut imports = imports::Imports::new(&env.library);
+        imports.add("glib::translate::*");
+
+        let functions = functions::analyze(
+            env,
+            &functions,
+            TypeId::tid_none(),
+            false,
+            false,
+            obj,
+            &mut imports,
+            None,
+            None,
+        );
 
-    let functions = functions::analyze(
-        env,
-        &functions,
-        TypeId::tid_none(),
-        false,
-        false,
-        obj,
-        &mut imports,
-        None,
-        None,
-    );
-
-    env.analysis.global_functions = Some(info_base::InfoBase {
-        full_name,
-        type_id: TypeId::tid_none(),
-        name: "*".into(),
-        functions,
-        imports,
-        ..Default::default()
-    });
+        infos.push(info_base::InfoBase {
+            full_name: full_name.clone(),
+            type_id: TypeId::tid_none(),
+            name: module.unwrap_or("*").into(),
+            functions,
+            imports,
+            ..Default::default()
+        });
+    }
+
+    for info in infos {
+        if info.name == "*" {
+            env.analysis.global_functions = Some(info);
+        } else {
+            env.analysis.global_functions_modules.push(info);
+        }
+    }
+}
+
+/// Returns the names of the modules generated for the classes, interfaces, records and unions.
+fn type_module_names(env: &Env) -> HashSet<String> {
+    env.analysis
+        .objects
+        .keys()
+        .chain(env.analysis.records.keys())
+        .chain(env.analysis.unions.keys())
+        .filter_map(|full_name| env.config.objects.get(full_name))
+        .filter(|obj| obj.status.need_generate())
+        .map(|obj| {
+            obj.module_name.clone().unwrap_or_else(|| {
+                nameutil::module_name(nameutil::split_namespace_name(&obj.name).1)
+            })
+        })
+        .collect()
 }
 
 fn analyze_constants(env: &mut Env) {
@@ -233,6 +292,11 @@ fn analyze(env: &mut Env, tid: TypeId, deps: &[TypeId]) {
                 env.analysis.records.insert(full_name, info);
             }
         }
+        Type::Union(_) => {
+            if let Some(info) = union::new(env, obj) {
+                env.analysis.unions.insert(full_name, info);
+            }
+        }
         _ => {}
     }
 }
@@ -250,3 +314,56 @@ fn is_all_deps_analyzed(env: &mut Env, deps: &[TypeId]) -> bool {
 pub fn is_gpointer(s: &str) -> bool {
     s == "gpointer" || s == "void*"
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::env::tests::env;
+
+    const URI: &str = r#"
+    <record name="Uri" c:type="TestUri" glib:type-name="TestUri" glib:get-type="test_uri_get_type" c:symbol-prefix="uri">
+    </record>
+    <function name="uri_escape" c:identifier="test_uri_escape">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+    </function>
+    <function name="unichar_validate" c:identifier="test_unichar_validate">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+    </function>
+"#;
+
+    const CONFIG: &str = r#"
+[[functions_module]]
+name = "uri"
+pattern = "^uri_"
+
+[[functions_module]]
+name = "unicode"
+pattern = "^unichar_"
+
+[[object]]
+name = "Test.*"
+status = "generate"
+
+[[object]]
+name = "Test.Uri"
+status = "generate"
+"#;
+
+    #[test]
+    fn functions_module_colliding_with_type_module() {
+        let t = env(URI, CONFIG);
+        let analysis = &t.env.analysis;
+        let names = |functions: &[crate::analysis::functions::Info]| {
+            functions.iter().map(|f| f.name.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(&analysis.global_functions.as_ref().unwrap().functions),
+            ["uri_escape"]
+        );
+        assert_eq!(analysis.global_functions_modules.len(), 1);
+        assert_eq!(analysis.global_functions_modules[0].name, "unicode");
+        assert_eq!(
+            names(&analysis.global_functions_modules[0].functions),
+            ["unichar_validate"]
+        );
+    }
+}
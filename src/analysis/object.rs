@@ -88,7 +88,7 @@ pub fn class(env: &Env, obj: &GObject, deps: &[library::TypeId]) -> Option<Info>
         Some(&mut signatures),
         Some(deps),
     );
-    let mut specials = special_functions::extract(&mut functions, type_, obj);
+    let mut specials = special_functions::extract(env, &mut functions, class_tid, obj);
     // `copy` will duplicate an object while `clone` just adds a reference
     special_functions::unhide(&mut functions, &specials, special_functions::Type::Copy);
     // these are all automatically derived on objects and compare by pointer. If such functions
@@ -172,7 +172,10 @@ pub fn class(env: &Env, obj: &GObject, deps: &[library::TypeId]) -> Option<Info>
         imports,
         version,
         deprecated_version,
-        cfg_condition: obj.cfg_condition.clone(),
+        cfg_condition: obj
+            .cfg_condition
+            .clone()
+            .or_else(|| env.config.cfg_condition_by_prefix(&klass.c_type)),
         concurrency: obj.concurrency,
     };
 
@@ -288,7 +291,10 @@ pub fn interface(env: &Env, obj: &GObject, deps: &[library::TypeId]) -> Option<I
         imports,
         version,
         deprecated_version,
-        cfg_condition: obj.cfg_condition.clone(),
+        cfg_condition: obj
+            .cfg_condition
+            .clone()
+            .or_else(|| env.config.cfg_condition_by_prefix(&iface.c_type)),
         concurrency: obj.concurrency,
     };
 
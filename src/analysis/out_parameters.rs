@@ -1,8 +1,12 @@
 use crate::{
     analysis::{
-        conversion_type::ConversionType, function_parameters::CParameter,
-        functions::is_carray_with_direct_elements, imports::Imports, ref_mode::RefMode,
-        return_value, rust_type::parameter_rust_type,
+        conversion_type::{is_direct_fixed_array, ConversionType},
+        function_parameters::CParameter,
+        functions::is_carray_with_direct_elements,
+        imports::Imports,
+        ref_mode::RefMode,
+        return_value,
+        rust_type::parameter_rust_type,
     },
     config,
     env::Env,
@@ -64,11 +68,13 @@ pub fn analyze(
     } else if func.ret.typ == TypeId::tid_none() {
         info.mode = Mode::Normal;
     } else if func.ret.typ == TypeId::tid_bool() {
-        if nullable_override == Some(Nullable(false)) {
-            info.mode = Mode::Combined;
-        } else {
-            info.mode = Mode::Optional;
-        }
+        // `bool_return_is_none` takes precedence over the older `nullable = false` switch
+        info.mode = match func_ret.bool_return_is_none {
+            Some(true) => Mode::Optional,
+            Some(false) => Mode::Combined,
+            None if nullable_override == Some(Nullable(false)) => Mode::Combined,
+            None => Mode::Optional,
+        };
     } else {
         info.mode = Mode::Combined;
     }
@@ -77,9 +83,17 @@ pub fn analyze(
         if par.direction != ParameterDirection::Out {
             continue;
         }
+        let name = nameutil::mangle_keywords(&*par.name);
+        // Caller-allocated buffers are passed by the caller as mutable slices
+        if func_c_params
+            .iter()
+            .any(|c_par| c_par.name == name && c_par.direction == ParameterDirection::In)
+        {
+            continue;
+        }
         if can_as_return(env, par) {
             let mut par = par.clone();
-            par.name = nameutil::mangle_keywords(&*par.name).into_owned();
+            par.name = name.into_owned();
             //TODO: temporary solution for string_type override
             if let Some(c_par) = func_c_params.iter().find(|c_par| c_par.name == par.name) {
                 par.typ = c_par.typ;
@@ -112,7 +126,9 @@ pub fn analyze_imports(env: &Env, parameters: &[Parameter], imports: &mut Import
     for par in parameters {
         if par.direction == ParameterDirection::Out {
             match *env.library.type_(par.typ) {
-                Type::Bitfield(..) | Type::Enumeration(..) => imports.add("std::mem"),
+                Type::Bitfield(..) | Type::Enumeration(..) | Type::FixedArray(..) => {
+                    imports.add("std::mem")
+                }
                 Type::Fundamental(fund)
                     if fund != Fundamental::Utf8
                         && fund != Fundamental::OsString
@@ -135,6 +151,8 @@ pub fn can_as_return(env: &Env, par: &Parameter) -> bool {
     match ConversionType::of(env, par.typ) {
         Direct => true,
         Scalar => true,
+        // Fixed arrays of numbers are copied out of a caller-allocated buffer
+        Pointer if is_direct_fixed_array(env, par.typ) => par.caller_allocates,
         Pointer => {
             // Disallow fundamental arrays without length
             if is_carray_with_direct_elements(env, par.typ) && par.array_length.is_none() {
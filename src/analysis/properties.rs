@@ -30,6 +30,9 @@ pub struct Property {
     pub set_bound: Option<PropertyBound>,
     pub version: Option<Version>,
     pub deprecated_version: Option<Version>,
+    /// Version from which the property is put behind the `deprecated` feature, the earliest of
+    /// its own deprecation and of its type's
+    pub deprecated_feature_version: Option<Version>,
 }
 
 pub fn analyze(
@@ -112,6 +115,8 @@ fn analyze_property(
     let generate_set = generate.is_some();
     let generate = generate.unwrap_or_else(PropertyGenerateFlags::all);
 
+    let deprecated_feature_version =
+        env.deprecated_feature_version(prop.deprecated_version, Some(prop.typ));
     let imports = &mut imports.with_defaults(prop_version, &None);
 
     let type_string = rust_type(env, prop.typ);
@@ -197,6 +202,7 @@ fn analyze_property(
             bounds: Bounds::default(),
             version: prop_version,
             deprecated_version: prop.deprecated_version,
+            deprecated_feature_version,
         })
     } else {
         None
@@ -231,6 +237,7 @@ fn analyze_property(
             bounds: Bounds::default(),
             version: prop_version,
             deprecated_version: prop.deprecated_version,
+            deprecated_feature_version,
         })
     } else {
         None
@@ -299,6 +306,7 @@ fn analyze_property(
                 action_emit_name: None,
                 version: prop_version,
                 deprecated_version: prop.deprecated_version,
+                deprecated_feature_version: prop.deprecated_version,
                 doc_hidden: false,
             })
         } else {
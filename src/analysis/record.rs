@@ -18,6 +18,8 @@ pub struct Info {
     pub base: InfoBase,
     pub glib_get_type: Option<(String, Option<Version>)>,
     pub use_boxed_functions: bool,
+    pub is_direct: bool,
+    pub fields: Vec<fields::Info>,
     pub derives: Derives,
     pub init_function_expression: Option<String>,
     pub clear_function_expression: Option<String>,
@@ -77,9 +79,18 @@ pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
 
     let record: &library::Record = type_.maybe_ref()?;
 
-    let is_boxed = obj.use_boxed_functions || RecordType::of(&record) == RecordType::AutoBoxed;
+    let record_type = if obj.use_boxed_functions {
+        RecordType::AutoBoxed
+    } else {
+        RecordType::of_object(env, record, obj)
+    };
+    let is_boxed = record_type == RecordType::AutoBoxed;
+    let is_direct = record_type == RecordType::Direct;
 
     let mut imports = Imports::with_defined(&env.library, &name);
+    if is_direct {
+        imports.add("glib::translate::*");
+    }
 
     let mut functions = functions::analyze(
         env,
@@ -92,7 +103,7 @@ pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
         None,
         None,
     );
-    let specials = special_functions::extract(&mut functions, type_, obj);
+    let specials = special_functions::extract(env, &mut functions, record_tid, obj);
 
     let (version, deprecated_version) = info_base::versions(
         env,
@@ -111,6 +122,9 @@ pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
 
     let mut derives = if let Some(ref derives) = obj.derives {
         derives.clone()
+    } else if is_direct {
+        // `Debug` is implemented from the field getters
+        Vec::new()
     } else {
         let derives = vec![Derive {
             names: vec![
@@ -144,6 +158,19 @@ pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
 
     special_functions::analyze_imports(&specials, &mut imports);
 
+    // Shared records can't be modified through a reference
+    let fields = fields::analyze(
+        env,
+        &record.fields,
+        obj,
+        &functions,
+        !is_shared,
+        &mut imports,
+    );
+    if !fields.is_empty() {
+        imports.add("glib::translate::*");
+    }
+
     let glib_get_type = if let Some(ref glib_get_type) = record.glib_get_type {
         let configured_functions = obj.functions.matched("get_type");
         let get_type_version = configured_functions
@@ -159,10 +186,12 @@ pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
 
     // Check if we have to make use of the GType and the generic
     // boxed functions.
-    if obj.use_boxed_functions
-        || !is_shared
-            && (!specials.has_trait(special_functions::Type::Copy)
-                || !specials.has_trait(special_functions::Type::Free))
+    // Direct records are copied by value and don't need any memory management functions.
+    if !is_direct
+        && (obj.use_boxed_functions
+            || !is_shared
+                && (!specials.has_trait(special_functions::Type::Copy)
+                    || !specials.has_trait(special_functions::Type::Free)))
     {
         if let Some((_, get_type_version)) = glib_get_type {
             if get_type_version > version {
@@ -194,7 +223,10 @@ pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
         imports,
         version,
         deprecated_version,
-        cfg_condition: obj.cfg_condition.clone(),
+        cfg_condition: obj
+            .cfg_condition
+            .clone()
+            .or_else(|| env.config.cfg_condition_by_prefix(&record.c_type)),
         concurrency: obj.concurrency,
     };
 
@@ -203,6 +235,8 @@ pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
         glib_get_type,
         derives,
         use_boxed_functions: obj.use_boxed_functions,
+        is_direct,
+        fields,
         init_function_expression: obj.init_function_expression.clone(),
         clear_function_expression: obj.clear_function_expression.clone(),
     };
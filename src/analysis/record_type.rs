@@ -1,4 +1,6 @@
-use crate::library;
+use super::conversion_type::ConversionType;
+use crate::{config::gobjects::GObject, env::Env, library};
+use log::warn;
 
 #[derive(PartialEq, Eq)]
 pub enum RecordType {
@@ -9,8 +11,8 @@ pub enum RecordType {
     Boxed,
     /// Referencecounted record
     Refcounted,
-    //TODO: detect and generate direct records
-    //Direct,
+    /// Plain C struct passed by value, without memory management functions
+    Direct,
 }
 
 impl RecordType {
@@ -43,4 +45,139 @@ impl RecordType {
             RecordType::AutoBoxed
         }
     }
+
+    /// Same as `of`, but records configured with `direct = true` are direct records if
+    /// all their fields can be passed by value.
+    pub fn of_object(env: &Env, record: &library::Record, obj: &GObject) -> RecordType {
+        if obj.direct == Some(true) {
+            if can_be_direct(env, record) {
+                return RecordType::Direct;
+            }
+            warn!(
+                "Record {} can't be direct, it has fields not passed by value",
+                obj.name
+            );
+        }
+        RecordType::of(record)
+    }
+}
+
+/// Whether the record `tid` is generated as a direct record, like `of_object` decides it but
+/// without warning.
+pub fn is_direct_record(env: &Env, tid: library::TypeId) -> bool {
+    let record = match env.type_(tid) {
+        library::Type::Record(record) => record,
+        _ => return false,
+    };
+    matches!(
+        env.config.objects.get(&tid.full_name(&env.library)),
+        Some(obj) if obj.status.need_generate()
+            && !obj.use_boxed_functions
+            && obj.direct == Some(true)
+    ) && can_be_direct(env, record)
+}
+
+fn can_be_direct(env: &Env, record: &library::Record) -> bool {
+    !record.disguised
+        && record.gtype_struct_for.is_none()
+        && !record.fields.is_empty()
+        && record
+            .fields
+            .iter()
+            .all(|field| is_direct_field(env, field))
+}
+
+/// Whether the field can be copied with the struct and converted by value.
+pub fn is_direct_field(env: &Env, field: &library::Field) -> bool {
+    field.bits.is_none()
+        && field.c_type.as_ref().filter(|c| !c.contains('*')).is_some()
+        && is_direct_type(env, field.typ)
+}
+
+fn is_direct_type(env: &Env, tid: library::TypeId) -> bool {
+    use crate::library::Type::*;
+    match *env.library.type_(tid) {
+        Fundamental(library::Fundamental::Pointer) => false,
+        Fundamental(_) | Enumeration(_) | Bitfield(_) => matches!(
+            ConversionType::of(env, tid),
+            ConversionType::Direct | ConversionType::Scalar
+        ),
+        Alias(ref alias) => is_direct_type(env, alias.typ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::tests::env;
+
+    const RECTANGLE: &str = r#"
+    <record name="Rectangle" c:type="TestRectangle">
+      <field name="x" writable="1"><type name="gint" c:type="gint"/></field>
+      <field name="width" writable="1"><type name="gdouble" c:type="gdouble"/></field>
+      <field name="visible" writable="1"><type name="gboolean" c:type="gboolean"/></field>
+    </record>
+    <record name="Named" c:type="TestNamed">
+      <field name="x" writable="1"><type name="gint" c:type="gint"/></field>
+      <field name="name" writable="1"><type name="utf8" c:type="gchar*"/></field>
+    </record>
+    <record name="Bits" c:type="TestBits">
+      <field name="flag" writable="1" bits="1"><type name="guint" c:type="guint"/></field>
+    </record>
+    <record name="Opaque" c:type="TestOpaque" disguised="1"/>"#;
+
+    fn record<'a>(env: &'a Env, name: &str) -> &'a library::Record {
+        let tid = env.library.find_type(0, name).unwrap();
+        match *env.library.type_(tid) {
+            library::Type::Record(ref record) => record,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn can_be_direct_only_with_fields_passed_by_value() {
+        let test = env(RECTANGLE, "");
+        let env = &test.env;
+        assert!(can_be_direct(env, record(env, "Test.Rectangle")));
+        assert!(!can_be_direct(env, record(env, "Test.Named")));
+        assert!(!can_be_direct(env, record(env, "Test.Bits")));
+        assert!(!can_be_direct(env, record(env, "Test.Opaque")));
+    }
+
+    #[test]
+    fn direct_records_are_opt_in() {
+        let test = env(
+            RECTANGLE,
+            r#"
+[[object]]
+name = "Test.Rectangle"
+status = "generate"
+
+[[object]]
+name = "Test.Named"
+status = "generate"
+direct = true
+"#,
+        );
+        let env = &test.env;
+        let rectangle = &env.config.objects["Test.Rectangle"];
+        assert!(
+            RecordType::of_object(env, record(env, "Test.Rectangle"), rectangle)
+                == RecordType::AutoBoxed
+        );
+        let direct = GObject {
+            direct: Some(true),
+            ..rectangle.clone()
+        };
+        assert!(
+            RecordType::of_object(env, record(env, "Test.Rectangle"), &direct)
+                == RecordType::Direct
+        );
+        // Can't be direct, keeps being boxed
+        let named = &env.config.objects["Test.Named"];
+        assert!(
+            RecordType::of_object(env, record(env, "Test.Named"), named) == RecordType::AutoBoxed
+        );
+    }
 }
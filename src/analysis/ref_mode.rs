@@ -42,7 +42,9 @@ impl RefMode {
             | List(..)
             | SList(..)
             | PtrArray(..)
-            | CArray(..) => {
+            | CArray(..)
+            | FixedArray(..)
+            | HashTable(..) => {
                 if direction == library::ParameterDirection::In {
                     RefMode::ByRef
                 } else {
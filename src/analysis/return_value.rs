@@ -1,7 +1,7 @@
 use crate::{
     analysis::{
-        imports::Imports, namespaces, override_string_type::override_string_type_return,
-        ref_mode::RefMode, rust_type::*,
+        function_parameters::is_caller_allocated_buffer, imports::Imports, namespaces,
+        override_string_type::override_string_type_return, ref_mode::RefMode, rust_type::*,
     },
     config,
     env::Env,
@@ -16,6 +16,11 @@ pub struct Info {
     pub commented: bool,
     pub bool_return_is_error: Option<String>,
     pub nullable_return_is_error: Option<String>,
+    pub int_return_is_error: Option<config::functions::IntReturnIsError>,
+    pub int_return_is_none: Option<i64>,
+    /// Configured choice between `Option<(A, B)>` and a `(bool, A, B)` tuple, `None` when
+    /// left at the default `Option`
+    pub bool_return_is_none: Option<bool>,
 }
 
 pub fn analyze(
@@ -123,6 +128,65 @@ pub fn analyze(
         }
     });
 
+    let int_return_is_error = configured_functions
+        .iter()
+        .find_map(|f| f.ret.int_return_is_error.as_ref());
+    let int_return_is_none = configured_functions
+        .iter()
+        .find_map(|f| f.ret.int_return_is_none);
+    let int_return_is_error = int_return_is_error.and_then(|int_return| {
+        let typ = analyze_int_sentinel(
+            env,
+            func,
+            typ,
+            configured_functions,
+            int_return.value,
+            "int_return_is_error",
+        )?;
+        let ns = if env.namespaces.glib_ns_id == namespaces::MAIN {
+            "crate::BoolError"
+        } else {
+            "glib"
+        };
+        imports.add(ns);
+        parameter.as_mut().unwrap().typ = typ;
+
+        Some(int_return.clone())
+    });
+    let int_return_is_none = int_return_is_none.and_then(|value| {
+        if int_return_is_error.is_some() {
+            error!(
+                "Ignoring int_return_is_none configuration for function {} with int_return_is_error",
+                func.name
+            );
+            return None;
+        }
+        parameter.as_mut().unwrap().typ = analyze_int_sentinel(
+            env,
+            func,
+            typ,
+            configured_functions,
+            value,
+            "int_return_is_none",
+        )?;
+
+        Some(value)
+    });
+
+    let bool_return_is_none = configured_functions
+        .iter()
+        .find_map(|f| f.ret.bool_return_is_none)
+        .filter(|_| {
+            if typ != TypeId::tid_bool() {
+                error!(
+                    "Ignoring bool_return_is_none configuration for non-bool returning function {}",
+                    func.name
+                );
+                return false;
+            }
+            true
+        });
+
     let mut base_tid = None;
 
     if func.kind == library::FunctionKind::Constructor {
@@ -148,6 +212,82 @@ pub fn analyze(
         commented,
         bool_return_is_error: bool_return_error_message,
         nullable_return_is_error: nullable_return_error_message,
+        int_return_is_error,
+        int_return_is_none,
+        bool_return_is_none,
+    }
+}
+
+/// Returns the type of the values left once the `value` sentinel is removed from the integer
+/// type `typ`: the unsigned type of the same size for a negative sentinel, `typ` otherwise.
+fn analyze_int_sentinel(
+    env: &Env,
+    func: &library::Function,
+    typ: TypeId,
+    configured_functions: &[&config::functions::Function],
+    value: i64,
+    option: &str,
+) -> Option<TypeId> {
+    use crate::library::{Fundamental, Type};
+
+    // Caller-allocated buffers are passed as input slices and don't become outs
+    let caller_allocated_buffers = configured_functions
+        .iter()
+        .any(|f| f.caller_allocated_buffers);
+    let has_outs = func.parameters.iter().any(|par| {
+        par.direction == library::ParameterDirection::Out
+            && !(caller_allocated_buffers && is_caller_allocated_buffer(env, par, par.typ))
+    });
+    if func.throws || has_outs {
+        error!(
+            "Ignoring {} configuration for function {} with out parameters",
+            option, func.name
+        );
+        return None;
+    }
+    let fund = match *env.library.type_(typ) {
+        Type::Fundamental(fund) => fund,
+        _ => Fundamental::Unsupported,
+    };
+    let unsigned = match fund {
+        Fundamental::Int8 => "guint8",
+        Fundamental::Int16 => "guint16",
+        Fundamental::Int32 => "guint32",
+        Fundamental::Int64 => "guint64",
+        Fundamental::Short => "gushort",
+        Fundamental::Int => "guint",
+        Fundamental::Long => "gulong",
+        Fundamental::SSize => "gsize",
+        Fundamental::UInt8
+        | Fundamental::UInt16
+        | Fundamental::UInt32
+        | Fundamental::UInt64
+        | Fundamental::UShort
+        | Fundamental::UInt
+        | Fundamental::ULong
+        | Fundamental::Size => {
+            if value < 0 {
+                error!(
+                    "Ignoring {} configuration with a negative value for function {} returning \
+                     an unsigned integer",
+                    option, func.name
+                );
+                return None;
+            }
+            return Some(typ);
+        }
+        _ => {
+            error!(
+                "Ignoring {} configuration for non-integer returning function {}",
+                option, func.name
+            );
+            return None;
+        }
+    };
+    if value < 0 {
+        env.library.find_type(0, unsigned)
+    } else {
+        Some(typ)
     }
 }
 
@@ -1,4 +1,4 @@
-use super::conversion_type::ConversionType;
+use super::conversion_type::{is_direct_fixed_array, ConversionType};
 use crate::{
     analysis::ref_mode::RefMode,
     env::Env,
@@ -208,6 +208,40 @@ pub fn rust_type_full(
                 }
             })
         }
+        FixedArray(inner_tid, size, _)
+            if ConversionType::of(env, inner_tid) == ConversionType::Pointer =>
+        {
+            skip_option = true;
+            let inner_ref_mode = match *env.library.type_(inner_tid) {
+                Class(..) | Interface(..) => RefMode::None,
+                _ => ref_mode,
+            };
+            rust_type_full(
+                env,
+                inner_tid,
+                Nullable(false),
+                inner_ref_mode,
+                scope,
+                concurrency,
+            )
+            .map_any(|s| format!("[{}; {}]", s, size))
+        }
+        FixedArray(inner_tid, size, _) if is_direct_fixed_array(env, type_id) => rust_type_full(
+            env,
+            inner_tid,
+            Nullable(false),
+            RefMode::None,
+            scope,
+            concurrency,
+        )
+        .map_any(|s| format!("[{}; {}]", s, size)),
+        HashTable(key_tid, value_tid)
+            if is_string_type(env, key_tid) && is_string_type(env, value_tid) =>
+        {
+            // glib only implements the conversions of hash tables with string keys and values
+            skip_option = true;
+            ok("HashMap<String, String>")
+        }
         CArray(inner_tid) if ConversionType::of(env, inner_tid) == ConversionType::Direct => {
             if let Fundamental(fund) = *env.library.type_(inner_tid) {
                 let array_type = match fund {
@@ -409,6 +443,10 @@ pub fn used_rust_type(env: &Env, type_id: library::TypeId, is_in: bool) -> Resul
             used_rust_type(env, inner_tid, false)
         }
         Custom(..) => rust_type(env, type_id),
+        // Imported by its full path
+        HashTable(..) if !is_unsupported_hash_table(env, type_id) => {
+            Ok("::std::collections::HashMap".to_owned())
+        }
         Fundamental(library::Fundamental::Utf8) if !is_in => Ok(use_glib_type(env, "GString")),
         _ => Err(TypeError::Ignored("Don't need use".to_owned())),
     }
@@ -476,7 +514,19 @@ pub fn parameter_rust_type(
             library::ParameterDirection::In | library::ParameterDirection::Return => rust_type,
             _ => Err(TypeError::Unimplemented(into_inner(rust_type))),
         },
-        CArray(..) | PtrArray(..) => match direction {
+        FixedArray(inner_tid, ..)
+            if direction == library::ParameterDirection::Out
+                || direction == library::ParameterDirection::Return =>
+        {
+            // Pointers to other types can't be copied into an array yet
+            if ConversionType::of(env, inner_tid) == ConversionType::Pointer {
+                Err(TypeError::Unimplemented(into_inner(rust_type)))
+            } else {
+                rust_type
+            }
+        }
+        FixedArray(..) if direction == library::ParameterDirection::In => rust_type,
+        CArray(..) | PtrArray(..) | HashTable(..) => match direction {
             library::ParameterDirection::In
             | library::ParameterDirection::Out
             | library::ParameterDirection::Return => rust_type,
@@ -491,6 +541,24 @@ pub fn parameter_rust_type(
     }
 }
 
+/// Whether the type is a hash table that can't be converted to a `HashMap`, as glib only
+/// implements the conversions of hash tables with string keys and values.
+pub fn is_unsupported_hash_table(env: &Env, type_id: library::TypeId) -> bool {
+    match *env.library.type_(type_id) {
+        library::Type::HashTable(key_tid, value_tid) => {
+            !is_string_type(env, key_tid) || !is_string_type(env, value_tid)
+        }
+        _ => false,
+    }
+}
+
+pub fn is_string_type(env: &Env, type_id: library::TypeId) -> bool {
+    matches!(
+        *env.library.type_(type_id),
+        library::Type::Fundamental(library::Fundamental::Utf8)
+    )
+}
+
 #[inline]
 fn format_parameter(rust_type: String, direction: library::ParameterDirection) -> String {
     if direction.is_out() {
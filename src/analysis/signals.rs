@@ -16,6 +16,9 @@ pub struct Info {
     pub trampoline: Result<Trampoline, Vec<String>>,
     pub version: Option<Version>,
     pub deprecated_version: Option<Version>,
+    /// Version from which the signal is put behind the `deprecated` feature, the earliest of
+    /// its own deprecation and of the gated types of its handler
+    pub deprecated_feature_version: Option<Version>,
     pub doc_hidden: bool,
 }
 
@@ -69,6 +72,14 @@ fn analyze_signal(
         .min()
         .or(signal.version);
     let deprecated_version = signal.deprecated_version;
+    let deprecated_feature_version = env.deprecated_feature_version(
+        deprecated_version,
+        signal
+            .parameters
+            .iter()
+            .chain(std::iter::once(&signal.ret))
+            .map(|par| par.typ),
+    );
     let doc_hidden = configured_signals.iter().any(|f| f.doc_hidden);
 
     let imports = &mut imports.with_defaults(version, &None);
@@ -113,6 +124,7 @@ fn analyze_signal(
         action_emit_name,
         version,
         deprecated_version,
+        deprecated_feature_version,
         doc_hidden,
     };
 
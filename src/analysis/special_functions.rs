@@ -1,12 +1,18 @@
 use crate::{
     analysis::{
+        conversion_type::ConversionType,
+        function_parameters::RustParameter,
         functions::{Info as FuncInfo, Visibility},
         imports::Imports,
+        out_parameters::Mode,
+        record_type,
     },
-    config::GObject,
-    library::{Type as LibType, TypeId},
+    config::{matchable::Matchable, GObject},
+    env::Env,
+    library::{FunctionKind, Fundamental, Transfer, Type as LibType, TypeId},
     version::Version,
 };
+use log::warn;
 use std::{collections::BTreeMap, str::FromStr};
 
 #[derive(Clone, Copy, Eq, Debug, Ord, PartialEq, PartialOrd)]
@@ -19,6 +25,42 @@ pub enum Type {
     Display,
     Unref,
     Hash,
+    Iterator,
+    IntoIterator,
+    FromStr,
+    Default,
+    Index,
+    AsRefStr,
+    From,
+    TryFrom,
+    Extend,
+}
+
+impl Type {
+    /// Parses the name of a trait implemented through a configured function
+    pub fn from_trait_name(name: &str) -> Option<Type> {
+        match name {
+            "PartialOrd" | "Ord" => Some(Type::Compare),
+            "PartialEq" | "Eq" => Some(Type::Equal),
+            "Display" => Some(Type::Display),
+            "Hash" => Some(Type::Hash),
+            "Iterator" => Some(Type::Iterator),
+            "IntoIterator" => Some(Type::IntoIterator),
+            "FromStr" => Some(Type::FromStr),
+            "Default" => Some(Type::Default),
+            "Index" => Some(Type::Index),
+            "AsRef<str>" => Some(Type::AsRefStr),
+            "From" => Some(Type::From),
+            "TryFrom" => Some(Type::TryFrom),
+            "Extend" => Some(Type::Extend),
+            _ => None,
+        }
+    }
+
+    /// Whether the trait can be implemented several times for different parameter types
+    fn is_repeatable(self) -> bool {
+        matches!(self, Type::From | Type::TryFrom | Type::Extend)
+    }
 }
 
 impl FromStr for Type {
@@ -65,6 +107,8 @@ type FunctionInfos = BTreeMap<String, FunctionInfo>;
 pub struct Infos {
     traits: TraitInfos,
     functions: FunctionInfos,
+    repeatable_traits: Vec<(Type, TraitInfo)>,
+    iterator_item: Option<String>,
 }
 
 impl Infos {
@@ -77,12 +121,22 @@ impl Infos {
     }
 
     pub fn has_trait(&self, type_: Type) -> bool {
-        self.traits.contains_key(&type_)
+        self.traits.contains_key(&type_) || self.repeatable_traits.iter().any(|(t, _)| *t == type_)
+    }
+
+    /// Traits like `From` implemented through several functions
+    pub fn repeatable_traits(&self) -> &[(Type, TraitInfo)] {
+        &self.repeatable_traits
     }
 
     pub fn functions(&self) -> &FunctionInfos {
         &self.functions
     }
+
+    /// Configured `Item` of the `Iterator` implementation
+    pub fn iterator_item(&self) -> Option<&str> {
+        self.iterator_item.as_deref()
+    }
 }
 
 /// Returns true on functions that take an instance as single argument and
@@ -121,6 +175,215 @@ fn is_stringify(func: &mut FuncInfo, parent_type: &LibType, obj: &GObject) -> bo
     }
 }
 
+/// Returns the parameters of a function besides the instance and a cancellable
+pub fn arguments(func: &FuncInfo) -> Vec<&RustParameter> {
+    func.parameters
+        .rust_parameters
+        .iter()
+        .filter(|par| {
+            par.name != "cancellable" && !func.parameters.c_parameters[par.ind_c].instance_parameter
+        })
+        .collect()
+}
+
+/// Returns true on methods without arguments besides an optional cancellable
+fn takes_no_arguments(func: &FuncInfo) -> bool {
+    func.kind == FunctionKind::Method && !func.r#async && arguments(func).is_empty()
+}
+
+/// Returns true on functions with a shape matching the configured trait of `type_tid`
+fn fits_trait(env: &Env, func: &FuncInfo, type_tid: TypeId, type_: Type) -> bool {
+    if func.r#async {
+        return false;
+    }
+    let is_method = func.kind == FunctionKind::Method;
+    let args = arguments(func);
+    let takes_self = matches!(args[..], [arg] if arg.typ == type_tid);
+    let ret_type = match func.ret.parameter {
+        Some(ref ret) if func.outs.is_empty() => Some(env.type_(ret.typ)),
+        _ => None,
+    };
+    let returns_int = matches!(
+        ret_type,
+        Some(LibType::Fundamental(Fundamental::Int))
+            | Some(LibType::Fundamental(Fundamental::Int32))
+    );
+    let returns_bool = matches!(ret_type, Some(LibType::Fundamental(Fundamental::Boolean)));
+    let returns_string = matches!(
+        func.ret.parameter,
+        Some(ref ret) if ret.typ == TypeId::tid_utf8() && !*ret.nullable
+    );
+    let returns_value =
+        matches!(func.ret.parameter, Some(ref ret) if ret.typ != TypeId::tid_none());
+    let returns_nothing = func.outs.is_empty() && !returns_value;
+    let returns_non_null = returns_value
+        && func.outs.is_empty()
+        && matches!(func.ret.parameter, Some(ref ret) if !*ret.nullable);
+    match type_ {
+        Type::Compare => is_method && takes_self && returns_int,
+        Type::Equal => is_method && takes_self && returns_bool,
+        Type::Hash => is_method && args.is_empty(),
+        Type::Display => is_method && args.is_empty() && returns_string && !func.ret.commented,
+        // The string must be borrowed from `self`
+        Type::AsRefStr => {
+            is_method
+                && args.is_empty()
+                && returns_string
+                && func.outs.is_empty()
+                && matches!(func.ret.parameter, Some(ref ret) if ret.transfer == Transfer::None)
+        }
+        Type::Iterator => is_iterator_next(func),
+        Type::IntoIterator => is_into_iterator(func),
+        Type::FromStr => !is_method && args.len() == 1 && args[0].typ == TypeId::tid_utf8(),
+        Type::Default => !is_method && args.is_empty() && returns_non_null,
+        Type::From => !is_method && args.len() == 1 && returns_non_null,
+        Type::TryFrom => !is_method && args.len() == 1 && returns_value,
+        // The returned pointer is referenced in place
+        Type::Index => {
+            is_method
+                && args.len() == 1
+                && ConversionType::of(env, args[0].typ) == ConversionType::Direct
+                && returns_non_null
+                && matches!(
+                    func.ret.parameter,
+                    Some(ref ret) if ret.transfer == Transfer::None
+                        && record_type::is_direct_record(env, ret.typ)
+                )
+        }
+        // Adds a single element, possibly reporting if it was added
+        Type::Extend => {
+            is_method
+                && args.len() == 1
+                && args[0].typ != type_tid
+                && !is_container(env.type_(args[0].typ))
+                && (returns_nothing || returns_bool)
+        }
+        Type::Copy | Type::Free | Type::Ref | Type::Unref => false,
+    }
+}
+
+/// Returns true on types holding several elements
+fn is_container(typ: &LibType) -> bool {
+    matches!(
+        typ,
+        LibType::Array(_)
+            | LibType::CArray(_)
+            | LibType::FixedArray(..)
+            | LibType::PtrArray(_)
+            | LibType::HashTable(..)
+            | LibType::List(_)
+            | LibType::SList(_)
+    )
+}
+
+/// Returns true on functions usable as `Iterator::next`: returning either a
+/// nullable value or a single value through an out parameter guarded by a
+/// boolean return, possibly throwing.
+fn is_iterator_next(func: &FuncInfo) -> bool {
+    if !takes_no_arguments(func) {
+        return false;
+    }
+    match func.outs.mode {
+        Mode::None => {
+            func.ret.nullable_return_is_error.is_none()
+                && matches!(
+                    func.ret.parameter,
+                    Some(ref ret) if *ret.nullable && ret.typ != TypeId::tid_bool()
+                )
+        }
+        Mode::Optional => func.outs.params.len() == 1,
+        // The returned value followed by the error
+        Mode::Throws(true) => func.outs.params.len() == 2 && *func.outs.params[0].nullable,
+        _ => false,
+    }
+}
+
+/// Returns true on functions returning the iterator of an owning container
+fn is_into_iterator(func: &FuncInfo) -> bool {
+    takes_no_arguments(func)
+        && func.outs.is_empty()
+        && matches!(func.ret.parameter, Some(ref ret) if !*ret.nullable)
+}
+
+fn extract_iterator(functions: &[FuncInfo], obj: &GObject, specials: &mut Infos) {
+    if let Some(ref config) = obj.iterator {
+        let next = functions.iter().find(|f| {
+            match config.next {
+                Some(ref name) => &f.name == name,
+                None => f.name == "next" || f.name.starts_with("next_"),
+            }
+        } && is_iterator_next(f));
+        if let Some(func) = next {
+            specials.traits.insert(
+                Type::Iterator,
+                TraitInfo {
+                    glib_name: func.glib_name.clone(),
+                    version: func.version,
+                },
+            );
+            specials.iterator_item = config.item.clone();
+        } else {
+            warn!("No function usable as Iterator::next in {}", obj.name);
+        }
+    }
+
+    if let Some(ref name) = obj.into_iterator {
+        match functions.iter().find(|f| &f.name == name) {
+            Some(func) if is_into_iterator(func) => {
+                specials.traits.insert(
+                    Type::IntoIterator,
+                    TraitInfo {
+                        glib_name: func.glib_name.clone(),
+                        version: func.version,
+                    },
+                );
+            }
+            _ => warn!(
+                "Function {} of {} can't be used as IntoIterator::into_iter",
+                name, obj.name
+            ),
+        }
+    }
+}
+
+fn extract_configured(
+    env: &Env,
+    functions: &mut [FuncInfo],
+    type_tid: TypeId,
+    obj: &GObject,
+    specials: &mut Infos,
+) {
+    for func in functions {
+        let type_ = match obj
+            .functions
+            .matched(&func.name)
+            .iter()
+            .find_map(|f| f.std_trait)
+        {
+            Some(type_) => type_,
+            None => continue,
+        };
+        if !fits_trait(env, func, type_tid, type_) {
+            warn!(
+                "Function {} of {} can't be used to implement {:?}",
+                func.name, obj.name, type_
+            );
+            continue;
+        }
+        // Hidden like the functions detected by their name
+        update_func(func, type_);
+        let info = TraitInfo {
+            glib_name: func.glib_name.clone(),
+            version: func.version,
+        };
+        if type_.is_repeatable() {
+            specials.repeatable_traits.push((type_, info));
+        } else {
+            specials.traits.insert(type_, info);
+        }
+    }
+}
+
 fn update_func(func: &mut FuncInfo, type_: Type) -> bool {
     if func.visibility != Visibility::Comment {
         func.visibility = visibility(type_);
@@ -128,7 +391,8 @@ fn update_func(func: &mut FuncInfo, type_: Type) -> bool {
     true
 }
 
-pub fn extract(functions: &mut Vec<FuncInfo>, parent_type: &LibType, obj: &GObject) -> Infos {
+pub fn extract(env: &Env, functions: &mut Vec<FuncInfo>, type_tid: TypeId, obj: &GObject) -> Infos {
+    let parent_type = env.type_(type_tid);
     let mut specials = Infos::default();
     let mut has_copy = false;
     let mut has_free = false;
@@ -214,6 +478,9 @@ pub fn extract(functions: &mut Vec<FuncInfo>, parent_type: &LibType, obj: &GObje
         }
     }
 
+    extract_iterator(functions, obj, &mut specials);
+    extract_configured(env, functions, type_tid, obj, &mut specials);
+
     specials
 }
 
@@ -222,7 +489,10 @@ fn visibility(t: Type) -> Visibility {
     match t {
         Copy | Free | Ref | Unref => Visibility::Hidden,
         Hash | Compare | Equal => Visibility::Private,
-        Display => Visibility::Public,
+        Display | Iterator | IntoIterator => Visibility::Public,
+        Type::FromStr | Type::Default | Index | AsRefStr | Type::From | TryFrom | Extend => {
+            Visibility::Public
+        }
     }
 }
 
@@ -239,12 +509,19 @@ pub fn unhide(functions: &mut Vec<FuncInfo>, specials: &Infos, type_: Type) {
 }
 
 pub fn analyze_imports(specials: &Infos, imports: &mut Imports) {
-    for (type_, info) in specials.traits() {
+    for (type_, info) in specials
+        .traits()
+        .iter()
+        .chain(specials.repeatable_traits().iter().map(|(t, i)| (t, i)))
+    {
         use self::Type::*;
         match *type_ {
             Compare => imports.add_with_version("std::cmp", info.version),
             Display => imports.add_with_version("std::fmt", info.version),
             Hash => imports.add_with_version("std::hash", info.version),
+            Type::FromStr => imports.add_with_version("std::str", info.version),
+            Index => imports.add_with_version("std::ops", info.version),
+            TryFrom => imports.add_with_version("std::convert::TryFrom", info.version),
             _ => {}
         }
     }
@@ -256,3 +533,235 @@ pub fn analyze_imports(specials: &Infos, imports: &mut Imports) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::tests::env;
+
+    const ITERATORS: &str = r#"
+    <class name="Item" c:symbol-prefix="item" c:type="TestItem" parent="GObject.Object" glib:type-name="TestItem" glib:get-type="test_item_get_type">
+    </class>
+    <class name="Enumerator" c:symbol-prefix="enumerator" c:type="TestEnumerator" parent="GObject.Object" glib:type-name="TestEnumerator" glib:get-type="test_enumerator_get_type">
+      <method name="next_item" c:identifier="test_enumerator_next_item" throws="1">
+        <return-value transfer-ownership="full" nullable="1"><type name="Item" c:type="TestItem*"/></return-value>
+        <parameters>
+          <instance-parameter name="enumerator" transfer-ownership="none"><type name="Enumerator" c:type="TestEnumerator*"/></instance-parameter>
+          <parameter name="cancellable" transfer-ownership="none" nullable="1" allow-none="1"><type name="Gio.Cancellable" c:type="GCancellable*"/></parameter>
+        </parameters>
+      </method>
+    </class>
+    <class name="ItemIter" c:symbol-prefix="item_iter" c:type="TestItemIter" parent="GObject.Object" glib:type-name="TestItemIter" glib:get-type="test_item_iter_get_type">
+      <method name="next" c:identifier="test_item_iter_next">
+        <return-value transfer-ownership="full" nullable="1"><type name="Item" c:type="TestItem*"/></return-value>
+        <parameters>
+          <instance-parameter name="iter" transfer-ownership="none"><type name="ItemIter" c:type="TestItemIter*"/></instance-parameter>
+        </parameters>
+      </method>
+    </class>
+    <class name="Cursor" c:symbol-prefix="cursor" c:type="TestCursor" parent="GObject.Object" glib:type-name="TestCursor" glib:get-type="test_cursor_get_type">
+      <method name="next_value" c:identifier="test_cursor_next_value">
+        <return-value transfer-ownership="none"><type name="gboolean" c:type="gboolean"/></return-value>
+        <parameters>
+          <instance-parameter name="cursor" transfer-ownership="none"><type name="Cursor" c:type="TestCursor*"/></instance-parameter>
+          <parameter name="value" direction="out" caller-allocates="0" transfer-ownership="full"><type name="gint" c:type="gint*"/></parameter>
+        </parameters>
+      </method>
+      <method name="next" c:identifier="test_cursor_next">
+        <return-value transfer-ownership="full" nullable="1"><type name="Item" c:type="TestItem*"/></return-value>
+        <parameters>
+          <instance-parameter name="cursor" transfer-ownership="none"><type name="Cursor" c:type="TestCursor*"/></instance-parameter>
+          <parameter name="skip" transfer-ownership="none"><type name="guint" c:type="guint"/></parameter>
+        </parameters>
+      </method>
+    </class>
+    <class name="TokenIter" c:symbol-prefix="token_iter" c:type="TestTokenIter" parent="GObject.Object" glib:type-name="TestTokenIter" glib:get-type="test_token_iter_get_type">
+      <method name="next" c:identifier="test_token_iter_next">
+        <return-value transfer-ownership="full" nullable="1"><type name="utf8" c:type="gchar*"/></return-value>
+        <parameters>
+          <instance-parameter name="iter" transfer-ownership="none"><type name="TokenIter" c:type="TestTokenIter*"/></instance-parameter>
+        </parameters>
+      </method>
+    </class>
+"#;
+    const CONFIG: &str = r#"
+[[object]]
+name = "Gio.Cancellable"
+status = "manual"
+
+[[object]]
+name = "Test.Item"
+status = "generate"
+
+[[object]]
+name = "Test.Enumerator"
+status = "generate"
+iterator = true
+
+[[object]]
+name = "Test.ItemIter"
+status = "generate"
+iterator = true
+
+[[object]]
+name = "Test.Cursor"
+status = "generate"
+iterator = { next = "next_value" }
+
+[[object]]
+name = "Test.TokenIter"
+status = "generate"
+"#;
+
+    fn is_next(t: &crate::env::tests::TestEnv, object: &str, function: &str) -> bool {
+        let info = &t.env.analysis.objects[object];
+        is_iterator_next(info.functions.iter().find(|f| f.name == function).unwrap())
+    }
+
+    #[test]
+    fn iterator_next_functions() {
+        let t = env(ITERATORS, CONFIG);
+        // Nullable return, possibly throwing and taking a cancellable
+        assert!(is_next(&t, "Test.Enumerator", "next_item"));
+        assert!(is_next(&t, "Test.ItemIter", "next"));
+        // Boolean return with a single out parameter
+        assert!(is_next(&t, "Test.Cursor", "next_value"));
+        // Other arguments
+        assert!(!is_next(&t, "Test.Cursor", "next"));
+    }
+
+    #[test]
+    fn guessed_iterator() {
+        let t = env(ITERATORS, CONFIG);
+        let code = t.generate("item_iter.rs");
+        assert!(code.contains(
+            "impl Iterator for ItemIter {\n    type Item = Item;\n\n    #[inline]\n    fn next(&mut self) -> Option<Self::Item> {\n        ItemIter::next(self)\n    }\n}"
+        ));
+        // Not configured, whatever its name
+        let code = t.generate("token_iter.rs");
+        assert!(!code.contains("impl Iterator for TokenIter"));
+    }
+
+    #[test]
+    fn configured_iterator() {
+        let code = env(ITERATORS, CONFIG).generate("cursor.rs");
+        assert!(code.contains(
+            "impl Iterator for Cursor {\n    type Item = i32;\n\n    #[inline]\n    fn next(&mut self) -> Option<Self::Item> {\n        Cursor::next_value(self)\n    }\n}"
+        ));
+    }
+
+    #[test]
+    fn fallible_iterator_ends_after_error() {
+        let code = env(ITERATORS, CONFIG).generate("enumerator.rs");
+        assert!(!code.contains("impl Iterator for Enumerator"));
+        assert!(code.contains(
+            "impl<'a> IntoIterator for &'a Enumerator {\n    type Item = Result<Item, glib::Error>;"
+        ));
+        assert!(code.contains(
+            ".map((|this: &'a Enumerator| Enumerator::next_item(this, gio::NONE_CANCELLABLE).transpose()) as fn(_) -> _)"
+        ));
+        assert!(code.contains("*failed = matches!(item, Some(Err(_)));"));
+    }
+
+    const STD_TRAITS: &str = r#"
+    <record name="Tag" c:symbol-prefix="tag" c:type="TestTag" glib:type-name="TestTag" glib:get-type="test_tag_get_type">
+      <method name="same_as" c:identifier="test_tag_same_as">
+        <return-value transfer-ownership="none"><type name="gboolean" c:type="gboolean"/></return-value>
+        <parameters>
+          <instance-parameter name="tag" transfer-ownership="none"><type name="Tag" c:type="const TestTag*"/></instance-parameter>
+          <parameter name="other" transfer-ownership="none"><type name="Tag" c:type="const TestTag*"/></parameter>
+        </parameters>
+      </method>
+      <method name="get_label" c:identifier="test_tag_get_label">
+        <return-value transfer-ownership="none"><type name="utf8" c:type="const gchar*"/></return-value>
+        <parameters>
+          <instance-parameter name="tag" transfer-ownership="none"><type name="Tag" c:type="const TestTag*"/></instance-parameter>
+        </parameters>
+      </method>
+      <method name="compare_name" c:identifier="test_tag_compare_name">
+        <return-value transfer-ownership="none"><type name="gint" c:type="gint"/></return-value>
+        <parameters>
+          <instance-parameter name="tag" transfer-ownership="none"><type name="Tag" c:type="const TestTag*"/></instance-parameter>
+          <parameter name="name" transfer-ownership="none"><type name="utf8" c:type="const gchar*"/></parameter>
+        </parameters>
+      </method>
+    </record>
+    <record name="Label" c:symbol-prefix="label" c:type="TestLabel" glib:type-name="TestLabel" glib:get-type="test_label_get_type">
+      <method name="dup_text" c:identifier="test_label_dup_text">
+        <return-value transfer-ownership="full"><type name="utf8" c:type="gchar*"/></return-value>
+        <parameters>
+          <instance-parameter name="label" transfer-ownership="none"><type name="Label" c:type="const TestLabel*"/></instance-parameter>
+        </parameters>
+      </method>
+      <method name="get_tag" c:identifier="test_label_get_tag">
+        <return-value transfer-ownership="none"><type name="Tag" c:type="TestTag*"/></return-value>
+        <parameters>
+          <instance-parameter name="label" transfer-ownership="none"><type name="Label" c:type="const TestLabel*"/></instance-parameter>
+          <parameter name="index" transfer-ownership="none"><type name="guint" c:type="guint"/></parameter>
+        </parameters>
+      </method>
+    </record>
+"#;
+    const STD_TRAITS_CONFIG: &str = r#"
+[[object]]
+name = "Test.Tag"
+status = "generate"
+    [[object.function]]
+    name = "same_as"
+    trait = "PartialEq"
+    [[object.function]]
+    name = "get_label"
+    trait = "AsRef<str>"
+        [object.function.return]
+        nullable = false
+    [[object.function]]
+    name = "compare_name"
+    trait = "PartialOrd"
+
+[[object]]
+name = "Test.Label"
+status = "generate"
+    [[object.function]]
+    name = "dup_text"
+    trait = "AsRef<str>"
+        [object.function.return]
+        nullable = false
+    [[object.function]]
+    name = "get_tag"
+    trait = "Index"
+        [object.function.return]
+        nullable = false
+"#;
+
+    #[test]
+    fn configured_equal_is_private() {
+        let code = env(STD_TRAITS, STD_TRAITS_CONFIG).generate("tag.rs");
+        assert!(code.contains("    fn same_as(&self, other: &Tag) -> bool {"));
+        assert!(!code.contains("pub fn same_as("));
+        assert!(code.contains("impl PartialEq for Tag {"));
+    }
+
+    #[test]
+    fn as_ref_str_borrows_utf8() {
+        let code = env(STD_TRAITS, STD_TRAITS_CONFIG).generate("tag.rs");
+        assert!(code.contains("impl AsRef<str> for Tag {"));
+        assert!(code.contains(
+            "std::ffi::CStr::from_ptr(ffi::test_tag_get_label(self.to_glib_none().0))\n                .to_str()\n                .expect(\"utf8 string\")"
+        ));
+    }
+
+    #[test]
+    fn mismatched_trait_functions_are_kept() {
+        let t = env(STD_TRAITS, STD_TRAITS_CONFIG);
+        // Not comparing with `Self`
+        let code = t.generate("tag.rs");
+        assert!(!code.contains("impl PartialOrd for Tag {"));
+        assert!(code.contains("    pub fn compare_name(&self, name: &str) -> i32 {"));
+        // Owned string and non-direct output
+        let code = t.generate("label.rs");
+        assert!(!code.contains("impl AsRef<str> for Label {"));
+        assert!(!code.contains("impl ops::Index<u32> for Label {"));
+        assert!(code.contains("    pub fn dup_text(&self) -> glib::GString {"));
+        assert!(code.contains("    pub fn get_tag(&self, index: u32) -> Tag {"));
+    }
+}
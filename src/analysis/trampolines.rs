@@ -213,6 +213,9 @@ pub fn type_error(env: &Env, par: &library::Parameter) -> Option<&'static str> {
         Some("Empty ctype")
     } else if ConversionType::of(env, par.typ) == ConversionType::Unknown {
         Some("Unknown conversion")
+    } else if matches!(*env.library.type_(par.typ), library::Type::FixedArray(..)) {
+        // Only function parameters and return values copy fixed arrays
+        Some("Fixed array")
     } else {
         match rust_type(env, par.typ) {
             Err(Ignored(_)) => Some("Ignored"),
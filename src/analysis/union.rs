@@ -0,0 +1,226 @@
+use super::{imports::Imports, info_base::InfoBase, record_type::is_direct_field, *};
+use crate::{
+    config::{derives::Derives, gobjects::GObject},
+    env::Env,
+    library,
+    nameutil::*,
+    traits::*,
+    version::Version,
+};
+use log::{info, warn};
+use std::ops::Deref;
+
+#[derive(Debug, Default)]
+pub struct Info {
+    pub base: InfoBase,
+    pub glib_get_type: Option<(String, Option<Version>)>,
+    pub is_direct: bool,
+    pub fields: Vec<fields::Info>,
+    /// Index in `fields` of the field telling which variant is valid
+    pub tag: Option<usize>,
+    pub derives: Derives,
+}
+
+impl Deref for Info {
+    type Target = InfoBase;
+
+    fn deref(&self) -> &InfoBase {
+        &self.base
+    }
+}
+
+impl Info {
+    pub fn type_<'a>(&self, library: &'a library::Library) -> &'a library::Union {
+        let type_ = library
+            .type_(self.type_id)
+            .maybe_ref()
+            .unwrap_or_else(|| panic!("{} is not an union.", self.full_name));
+        type_
+    }
+
+    pub fn c_type<'a>(&'a self, library: &'a library::Library) -> &'a str {
+        self.type_(library)
+            .c_type
+            .as_ref()
+            .map_or(&self.name, String::as_str)
+    }
+}
+
+pub fn new(env: &Env, obj: &GObject) -> Option<Info> {
+    info!("Analyzing union {}", obj.name);
+    let full_name = obj.name.clone();
+
+    let union_tid = env.library.find_type(0, &full_name)?;
+
+    let type_ = env.type_(union_tid);
+
+    let name: String = split_namespace_name(&full_name).1.into();
+
+    let union: &library::Union = type_.maybe_ref()?;
+
+    let can_be_direct =
+        !union.fields.is_empty() && union.fields.iter().all(|field| is_direct_field(env, field));
+    // Like records, unions are only direct when configured with `direct = true`
+    let is_direct = obj.direct == Some(true) && can_be_direct;
+    if obj.direct == Some(true) && !can_be_direct {
+        warn!(
+            "Union {} can't be direct, it has fields not passed by value",
+            full_name
+        );
+    }
+
+    let mut imports = Imports::with_defined(&env.library, &name);
+    if is_direct {
+        imports.add("glib::translate::*");
+    }
+
+    let mut functions = functions::analyze(
+        env,
+        &union.functions,
+        union_tid,
+        false,
+        false,
+        obj,
+        &mut imports,
+        None,
+        None,
+    );
+    let specials = special_functions::extract(env, &mut functions, union_tid, obj);
+
+    let (version, deprecated_version) = info_base::versions(env, obj, &functions, None, None);
+
+    let derives = if let Some(ref derives) = obj.derives {
+        derives.clone()
+    } else {
+        // Reading the variants is unsafe, `Debug` only shows the tag if there is one
+        Vec::new()
+    };
+
+    special_functions::analyze_imports(&specials, &mut imports);
+
+    // Writing a variant of a tagged union could make it inconsistent with the tag
+    let mut fields = fields::analyze(
+        env,
+        &union.fields,
+        obj,
+        &functions,
+        obj.union_tag.is_none(),
+        &mut imports,
+    );
+    if !fields.is_empty() {
+        imports.add("glib::translate::*");
+    }
+
+    let tag = match obj.union_tag {
+        Some(ref tag_name) => Some(analyze_tag(env, &full_name, tag_name, &mut fields)?),
+        None => {
+            if let Some(field) = fields.iter().find(|f| !f.tag_values.is_empty()) {
+                warn!(
+                    "Field {}.{} has tag_values but the union has no tag",
+                    full_name, field.name
+                );
+            }
+            None
+        }
+    };
+
+    let glib_get_type = if let Some(ref glib_get_type) = union.glib_get_type {
+        let configured_functions = obj.functions.matched("get_type");
+        let get_type_version = configured_functions
+            .iter()
+            .map(|f| f.version)
+            .max()
+            .flatten();
+
+        Some((glib_get_type.clone(), get_type_version))
+    } else {
+        None
+    };
+
+    if !is_direct
+        && glib_get_type.is_none()
+        && (!specials.has_trait(special_functions::Type::Copy)
+            || !specials.has_trait(special_functions::Type::Free))
+    {
+        error!("Missing memory management functions for {}", full_name);
+        return None;
+    }
+
+    let base = InfoBase {
+        full_name,
+        type_id: union_tid,
+        name,
+        functions,
+        specials,
+        imports,
+        version,
+        deprecated_version,
+        cfg_condition: obj.cfg_condition.clone().or_else(|| {
+            union
+                .c_type
+                .as_ref()
+                .and_then(|c_type| env.config.cfg_condition_by_prefix(c_type))
+        }),
+        concurrency: obj.concurrency,
+    };
+
+    let info = Info {
+        base,
+        glib_get_type,
+        is_direct,
+        fields,
+        tag,
+        derives,
+    };
+
+    Some(info)
+}
+
+/// Finds the tag field and resolves the `tag_values` of the other fields to enum variants.
+fn analyze_tag(
+    env: &Env,
+    full_name: &str,
+    tag_name: &str,
+    fields: &mut [fields::Info],
+) -> Option<usize> {
+    // Field names are already mangled by the library postprocessing
+    let mangled_name = mangle_keywords(tag_name);
+    let pos = match fields
+        .iter()
+        .position(|f| f.name == mangled_name && f.getter.is_some())
+    {
+        Some(pos) => pos,
+        None => {
+            error!("Missing tag field {} for union {}", tag_name, full_name);
+            return None;
+        }
+    };
+    let enum_ = match *env.library.type_(fields[pos].typ) {
+        library::Type::Enumeration(ref enum_) => enum_,
+        _ => {
+            error!(
+                "Tag field {} of union {} isn't an enumeration",
+                tag_name, full_name
+            );
+            return None;
+        }
+    };
+    let enum_name = fields[pos].rust_type.clone();
+
+    for field in fields.iter_mut() {
+        let mut values = Vec::with_capacity(field.tag_values.len());
+        for value in &field.tag_values {
+            if enum_.members.iter().any(|m| &m.name == value) {
+                values.push(format!("{}::{}", enum_name, enum_member_name(value)));
+            } else {
+                warn!(
+                    "Unknown tag value {} for field {}.{}",
+                    value, full_name, field.name
+                );
+            }
+        }
+        field.tag_values = values;
+    }
+
+    Some(pos)
+}
@@ -48,6 +48,8 @@ pub enum Chunk {
     },
     OptionalReturn {
         condition: String,
+        //Only run when the condition holds, e.g. reading the out parameters
+        init: Vec<Chunk>,
         value: Box<Chunk>,
     },
     ErrorResultReturn {
@@ -1,12 +1,14 @@
 use crate::{
-    analysis::{namespaces, rust_type::rust_type},
+    analysis::{conversion_type::ConversionType, namespaces, rust_type::rust_type},
     codegen::general,
     config::gobjects::GObject,
     env::Env,
     file_saver,
     library::*,
+    nameutil::use_glib_type,
     traits::*,
 };
+use log::warn;
 use std::{
     io::{prelude::*, Result},
     path::Path,
@@ -39,13 +41,23 @@ pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
         writeln!(w)?;
         writeln!(w, "#[allow(unused_imports)]")?;
         writeln!(w, "use crate::auto::*;")?;
+        if configs
+            .iter()
+            .any(|config| env.is_newtype_alias(config.type_id.unwrap()))
+        {
+            writeln!(w, "use {};", use_glib_type(env, "translate::*"))?;
+        }
         writeln!(w)?;
 
         mod_rs.push("\nmod alias;".into());
         for config in &configs {
             if let Type::Alias(ref alias) = *env.library.type_(config.type_id.unwrap()) {
                 mod_rs.push(format!("pub use self::alias::{};", alias.name));
-                generate_alias(env, w, alias, config)?;
+                if env.is_newtype_alias(config.type_id.unwrap()) {
+                    generate_newtype(env, w, alias, config)?;
+                } else {
+                    generate_alias(env, w, alias, config)?;
+                }
             }
         }
 
@@ -53,9 +65,146 @@ pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
     });
 }
 
-fn generate_alias(env: &Env, w: &mut dyn Write, alias: &Alias, _: &GObject) -> Result<()> {
+fn generate_alias(env: &Env, w: &mut dyn Write, alias: &Alias, config: &GObject) -> Result<()> {
+    if config.newtype {
+        warn!(
+            "Alias {} can't be a newtype, only aliases of types passed by value can",
+            config.name
+        );
+    }
     let typ = rust_type(env, alias.typ).into_string();
     writeln!(w, "pub type {} = {};", alias.name, typ)?;
 
     Ok(())
 }
+
+fn generate_newtype(env: &Env, w: &mut dyn Write, alias: &Alias, config: &GObject) -> Result<()> {
+    let typ = rust_type(env, alias.typ).into_string();
+    let ffi_name = format!("{}::{}", env.main_sys_crate_name(), alias.c_identifier);
+    let to_glib = match ConversionType::of(env, alias.typ) {
+        ConversionType::Scalar => "self.0.to_glib()",
+        _ => "self.0",
+    };
+    let from_glib = match ConversionType::of(env, alias.typ) {
+        ConversionType::Scalar => "from_glib(value)",
+        _ => "value",
+    };
+    let assert = if env.config.generate_safety_asserts {
+        "skip_assert_initialized!();\n\t\t"
+    } else {
+        ""
+    };
+
+    if let Some(ref d) = config.derives {
+        general::derives_with_defaults(w, d, &["Clone", "Copy"], 0)?;
+    } else {
+        if typ == "f32" || typ == "f64" {
+            writeln!(w, "#[derive(Debug, PartialEq, PartialOrd)]")?;
+        } else {
+            writeln!(w, "#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]")?;
+        }
+        writeln!(w, "#[derive(Clone, Copy)]")?;
+    }
+    writeln!(w, "#[repr(transparent)]")?;
+    general::doc_alias(w, &alias.c_identifier, "", 0)?;
+    writeln!(
+        w,
+        "pub struct {name}(pub {typ});
+
+impl std::ops::Deref for {name} {{
+    type Target = {typ};
+
+    fn deref(&self) -> &{typ} {{
+        &self.0
+    }}
+}}
+
+#[doc(hidden)]
+impl ToGlib for {name} {{
+    type GlibType = {ffi_name};
+
+    fn to_glib(&self) -> {ffi_name} {{
+        {to_glib}
+    }}
+}}
+
+#[doc(hidden)]
+impl FromGlib<{ffi_name}> for {name} {{
+    unsafe fn from_glib(value: {ffi_name}) -> Self {{
+        {assert}Self({from_glib})
+    }}
+}}
+",
+        name = alias.name,
+        typ = typ,
+        ffi_name = ffi_name,
+        to_glib = to_glib,
+        from_glib = from_glib,
+        assert = assert,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::env::tests::env;
+
+    const ALIASES: &str = r#"
+    <alias name="Id" c:type="TestId"><type name="guint32" c:type="guint32"/></alias>
+    <alias name="Scale" c:type="TestScale"><type name="gdouble" c:type="gdouble"/></alias>
+    <alias name="Flag" c:type="TestFlag"><type name="guint32" c:type="guint32"/></alias>
+    <alias name="Count" c:type="TestCount"><type name="guint32" c:type="guint32"/></alias>
+"#;
+    const CONFIG: &str = r#"
+[[object]]
+name = "Test.Id"
+status = "generate"
+newtype = true
+
+[[object]]
+name = "Test.Scale"
+status = "generate"
+newtype = true
+
+[[object]]
+name = "Test.Flag"
+status = "generate"
+newtype = true
+    [[object.derive]]
+    name = "Debug, Clone, PartialEq"
+    [[object.derive]]
+    name = "Copy"
+    cfg_condition = "feature = \"copy\""
+
+[[object]]
+name = "Test.Count"
+status = "generate"
+"#;
+
+    #[test]
+    fn parse_newtype() {
+        let t = env(ALIASES, CONFIG);
+        assert!(t.env.config.objects["Test.Id"].newtype);
+        assert!(!t.env.config.objects["Test.Count"].newtype);
+    }
+
+    #[test]
+    fn newtype_default_derives() {
+        let code = env(ALIASES, CONFIG).generate("alias.rs");
+        assert!(code.contains(
+            "#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]\n#[derive(Clone, Copy)]\n#[repr(transparent)]\n#[doc(alias = \"TestId\")]\npub struct Id(pub u32);"
+        ));
+        assert!(code.contains(
+            "#[derive(Debug, PartialEq, PartialOrd)]\n#[derive(Clone, Copy)]\n#[repr(transparent)]\n#[doc(alias = \"TestScale\")]\npub struct Scale(pub f64);"
+        ));
+        assert!(code.contains("pub type Count = u32;"));
+    }
+
+    #[test]
+    fn newtype_configured_derives() {
+        let code = env(ALIASES, CONFIG).generate("alias.rs");
+        // `Clone` and `Copy` are only derived as configured
+        assert!(code.contains(
+            "#[derive(Debug, Clone, PartialEq)]\n#[cfg_attr(feature = \"copy\", derive(Copy))]\n#[repr(transparent)]\n#[doc(alias = \"TestFlag\")]\npub struct Flag(pub u32);"
+        ));
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{analysis::functions::Info, env::Env, file_saver::save_to_file};
+use log::{info, warn};
+use std::{fs, io};
+use toml::{self, value::Table, Value};
+
+/// A key the generated code needs in a table of the crate's Cargo.toml.
+struct Declaration {
+    table: &'static str,
+    key: &'static str,
+    value: &'static str,
+}
+
+/// Crates needed by the generated `*_stream()` functions.
+const STREAM_DEPENDENCIES: &[Declaration] = &[
+    Declaration {
+        table: "dependencies",
+        key: "futures-core",
+        value: "\"0.3\"",
+    },
+    Declaration {
+        table: "dependencies",
+        key: "futures-util",
+        value: "\"0.3\"",
+    },
+];
+
+/// Optional dependency, and so feature, of the generated serde implementations.
+const SERDE_DEPENDENCY: Declaration = Declaration {
+    table: "dependencies",
+    key: "serde",
+    value: "{ version = \"1\", optional = true, features = [\"derive\"] }",
+};
+
+const DEPRECATED_FEATURE: Declaration = Declaration {
+    table: "features",
+    key: "deprecated",
+    value: "[]",
+};
+
+/// Declares the features and dependencies the generated code relies on in the crate's
+/// existing Cargo.toml. The missing keys are inserted in the file as is, so its comments and
+/// formatting are kept.
+pub fn generate(env: &Env) -> io::Result<()> {
+    let mut declarations = Vec::new();
+    if env.config.deprecated_feature {
+        declarations.push(&DEPRECATED_FEATURE);
+    }
+    if has_streams(env) {
+        declarations.extend(STREAM_DEPENDENCIES);
+    }
+    if has_serde(env) {
+        declarations.push(&SERDE_DEPENDENCY);
+    }
+    if declarations.is_empty() {
+        return Ok(());
+    }
+
+    let path = env.config.target_path.join("Cargo.toml");
+    let toml_str = match fs::read_to_string(&path) {
+        Ok(toml_str) => toml_str,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let root_table: Table = match toml::from_str(&toml_str) {
+        Ok(table) if !toml_str.trim().is_empty() => table,
+        _ => {
+            for declaration in &declarations {
+                warn!(
+                    "No valid Cargo.toml found at {}, `{}` must be declared manually in `[{}]`",
+                    path.display(),
+                    declaration.key,
+                    declaration.table
+                );
+            }
+            return Ok(());
+        }
+    };
+
+    let mut lines: Vec<String> = toml_str.lines().map(ToOwned::to_owned).collect();
+    let mut changed = false;
+    for declaration in declarations {
+        if is_declared(&root_table, declaration) {
+            continue;
+        }
+        info!(
+            "Declaring `{}` in the `[{}]` of {}",
+            declaration.key,
+            declaration.table,
+            path.display()
+        );
+        insert(&mut lines, declaration);
+        changed = true;
+    }
+    if !changed {
+        return Ok(());
+    }
+
+    save_to_file(&path, env.config.make_backup, |w| {
+        for line in &lines {
+            writeln!(w, "{}", line)?;
+        }
+        Ok(())
+    });
+    Ok(())
+}
+
+/// Whether the key is already in its table, dependencies are also looked for in the
+/// platform specific tables and under their `package` name.
+fn is_declared(root_table: &Table, declaration: &Declaration) -> bool {
+    let declared_in = |table: Option<&Value>| match table.and_then(Value::as_table) {
+        Some(table) => table.iter().any(|(key, value)| {
+            key == declaration.key
+                || value.get("package").and_then(Value::as_str) == Some(declaration.key)
+        }),
+        None => false,
+    };
+    if declared_in(root_table.get(declaration.table)) {
+        return true;
+    }
+    declaration.table == "dependencies"
+        && root_table
+            .get("target")
+            .and_then(Value::as_table)
+            .into_iter()
+            .flat_map(|targets| targets.values())
+            .any(|target| declared_in(target.get("dependencies")))
+}
+
+/// Inserts the key after the last one of its table, or in a new table at the end of the file.
+fn insert(lines: &mut Vec<String>, declaration: &Declaration) {
+    let line = format!("{} = {}", declaration.key, declaration.value);
+    let header = format!("[{}]", declaration.table);
+    let start = match lines
+        .iter()
+        .position(|l| table_header(l) == Some(header.as_str()))
+    {
+        Some(start) => start,
+        None => {
+            if matches!(lines.last(), Some(l) if !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(header);
+            lines.push(line);
+            return;
+        }
+    };
+    let end = lines[start + 1..]
+        .iter()
+        .position(|l| table_header(l).is_some())
+        .map_or(lines.len(), |pos| start + 1 + pos);
+    let last_key = lines[start..end]
+        .iter()
+        .rposition(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .map_or(start, |pos| start + pos);
+    lines.insert(last_key + 1, line);
+}
+
+/// The `[table]` or `[[table]]` header on the line, without its comment.
+fn table_header(line: &str) -> Option<&str> {
+    let line = line.split('#').next().unwrap().trim();
+    if line.starts_with('[') && line.ends_with(']') {
+        Some(line)
+    } else {
+        None
+    }
+}
+
+fn has_streams(env: &Env) -> bool {
+    let analysis = &env.analysis;
+    let has_stream = |functions: &[Info]| {
+        functions
+            .iter()
+            .any(|f| matches!(f.async_future, Some(ref future) if future.stream.is_some()))
+    };
+    analysis.objects.values().any(|o| has_stream(&o.functions))
+        || analysis.records.values().any(|r| has_stream(&r.functions))
+        || analysis.unions.values().any(|u| has_stream(&u.functions))
+        || analysis
+            .global_functions
+            .iter()
+            .any(|g| has_stream(&g.functions))
+}
+
+fn has_serde(env: &Env) -> bool {
+    let analysis = &env.analysis;
+    let config = &env.config;
+    analysis
+        .enumerations
+        .iter()
+        .any(|e| config.generate_serde(&e.full_name))
+        || analysis
+            .flags
+            .iter()
+            .any(|f| config.generate_serde(&f.full_name))
+        || analysis
+            .records
+            .values()
+            .any(|r| r.is_direct && config.generate_serde(&r.full_name))
+}
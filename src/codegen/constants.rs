@@ -1,7 +1,9 @@
 use crate::{
     analysis::imports::Imports,
     codegen::general::{
-        self, cfg_condition, cfg_deprecated, doc_alias, version_condition, version_condition_string,
+        self, cfg_condition, cfg_deprecated, deprecated_feature_condition,
+        deprecated_feature_condition_string, doc_alias, version_condition,
+        version_condition_string,
     },
     env::Env,
     file_saver, library,
@@ -30,6 +32,7 @@ pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
             let type_ = env.type_(constant.typ);
             if let library::Type::Fundamental(library::Fundamental::Utf8) = *type_ {
                 cfg_deprecated(w, env, constant.deprecated_version, false, 0)?;
+                deprecated_feature_condition(w, env, constant.deprecated_version, false, 0)?;
                 cfg_condition(w, &constant.cfg_condition, false, 0)?;
                 version_condition(w, env, constant.version, false, 0)?;
                 doc_alias(w, &constant.glib_name, "", 0)?;
@@ -41,6 +44,11 @@ pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
                     name = constant.name,
                     c_id = constant.glib_name
                 )?;
+                if let Some(cfg) =
+                    deprecated_feature_condition_string(env, constant.deprecated_version, false, 0)
+                {
+                    mod_rs.push(cfg);
+                }
                 if let Some(cfg) = version_condition_string(env, constant.version, false, 0) {
                     mod_rs.push(cfg);
                 }
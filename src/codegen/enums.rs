@@ -3,8 +3,9 @@ use crate::{
     analysis::enums::Info,
     analysis::special_functions::Type,
     codegen::general::{
-        self, cfg_deprecated, derives, doc_alias, version_condition, version_condition_no_doc,
-        version_condition_string,
+        self, cfg_deprecated, deprecated_feature_condition, deprecated_feature_condition_no_doc,
+        deprecated_feature_condition_string, derives, doc_alias, version_condition,
+        version_condition_no_doc, version_condition_string,
     },
     config::gobjects::GObject,
     env::Env,
@@ -36,6 +37,11 @@ pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
             let config = &env.config.objects[&enum_analysis.full_name];
             let enum_ = enum_analysis.type_(&env.library);
 
+            if let Some(cfg) =
+                deprecated_feature_condition_string(env, enum_.deprecated_version, false, 0)
+            {
+                mod_rs.push(cfg);
+            }
             if let Some(cfg) = version_condition_string(env, enum_.version, false, 0) {
                 mod_rs.push(cfg);
             }
@@ -58,6 +64,7 @@ fn generate_enum(
     struct Member {
         name: String,
         c_name: String,
+        nick: String,
         value: String,
         version: Option<Version>,
         deprecated_version: Option<Version>,
@@ -83,6 +90,7 @@ fn generate_enum(
         members.push(Member {
             name: enum_member_name(&member.name),
             c_name: member.c_identifier.clone(),
+            nick: member.nick.clone(),
             value: member.value.clone(),
             version,
             deprecated_version,
@@ -90,6 +98,7 @@ fn generate_enum(
     }
 
     cfg_deprecated(w, env, enum_.deprecated_version, false, 0)?;
+    deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
     version_condition(w, env, enum_.version, false, 0)?;
     if config.must_use {
         writeln!(w, "#[must_use]")?;
@@ -107,6 +116,7 @@ fn generate_enum(
     writeln!(w, "pub enum {} {{", enum_.name)?;
     for member in &members {
         cfg_deprecated(w, env, member.deprecated_version, false, 1)?;
+        deprecated_feature_condition(w, env, member.deprecated_version, false, 1)?;
         version_condition(w, env, member.version, false, 1)?;
         doc_alias(w, &member.c_name, "", 1)?;
         writeln!(w, "\t{},", member.name)?;
@@ -127,6 +137,7 @@ fn generate_enum(
 
     if !functions.is_empty() {
         writeln!(w)?;
+        deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
         version_condition(w, env, enum_.version, false, 0)?;
         write!(w, "impl {} {{", analysis.name)?;
         for func_analysis in functions {
@@ -156,6 +167,7 @@ fn generate_enum(
 
     if config.generate_display_trait && !analysis.specials.has_trait(Type::Display) {
         // Generate Display trait implementation.
+        deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
         version_condition(w, env, enum_.version, false, 0)?;
         writeln!(
             w,
@@ -165,6 +177,7 @@ fn generate_enum(
             enum_.name
         )?;
         for member in &members {
+            deprecated_feature_condition_no_doc(w, env, member.deprecated_version, false, 3)?;
             version_condition_no_doc(w, env, member.version, false, 3)?;
             writeln!(w, "\t\t\t{0}::{1} => \"{1}\",", enum_.name, member.name)?;
         }
@@ -177,7 +190,167 @@ fn generate_enum(
         )?;
     }
 
+    // Generate the tables of the C names and nicks of the members, used by
+    // `FromStr` to parse either of them.
+    let tables = [("name", "C name"), ("nick", "nick")];
+    if analysis.generate_tables {
+        deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
+        version_condition(w, env, enum_.version, false, 0)?;
+        writeln!(w, "impl {} {{", enum_.name)?;
+        for (i, &(field, doc)) in tables.iter().enumerate() {
+            if i > 0 {
+                writeln!(w)?;
+            }
+            let values = members
+                .iter()
+                .map(|m| if field == "name" { &m.c_name } else { &m.nick })
+                .collect::<Vec<_>>();
+            writeln!(
+                w,
+                "    /// Returns the {doc} of the value, `None` if it is unknown.
+    pub fn {field}(&self) -> Option<&'static str> {{
+        match *self {{",
+                doc = doc,
+                field = field
+            )?;
+            for (member, value) in members.iter().zip(&values) {
+                deprecated_feature_condition_no_doc(w, env, member.deprecated_version, false, 3)?;
+                version_condition_no_doc(w, env, member.version, false, 3)?;
+                writeln!(
+                    w,
+                    "\t\t\t{}::{} => Some(\"{}\"),",
+                    enum_.name, member.name, value
+                )?;
+            }
+            writeln!(
+                w,
+                "            _ => None,
+        }}
+    }}
+
+    /// Returns the value with the given {doc}.
+    pub fn from_{field}({field}: &str) -> Option<Self> {{
+        match {field} {{",
+                doc = doc,
+                field = field
+            )?;
+            for (member, value) in members.iter().zip(&values) {
+                deprecated_feature_condition_no_doc(w, env, member.deprecated_version, false, 3)?;
+                version_condition_no_doc(w, env, member.version, false, 3)?;
+                writeln!(
+                    w,
+                    "\t\t\t\"{}\" => Some({}::{}),",
+                    value, enum_.name, member.name
+                )?;
+            }
+            writeln!(
+                w,
+                "            _ => None,
+        }}
+    }}"
+            )?;
+        }
+        writeln!(w, "}}")?;
+        writeln!(w)?;
+    }
+
+    if analysis.generate_tables && !analysis.specials.has_trait(Type::FromStr) {
+        deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
+        version_condition(w, env, enum_.version, false, 0)?;
+        writeln!(
+            w,
+            "\
+impl str::FromStr for {name} {{
+    type Err = {bool_error};
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {{
+        Self::from_nick(s)
+            .or_else(|| Self::from_name(s))
+            .ok_or_else(|| {bool_error_macro}(\"Invalid {name} value: {{}}\", s))
+    }}
+}}
+",
+            name = enum_.name,
+            bool_error = use_glib_type(env, "BoolError"),
+            bool_error_macro = use_glib_type(env, "bool_error!"),
+        )?;
+    }
+
+    if env.config.generate_serde(&analysis.full_name) {
+        deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
+        version_condition(w, env, enum_.version, false, 0)?;
+        writeln!(
+            w,
+            "\
+#[cfg(feature = \"serde\")]
+impl serde::Serialize for {name} {{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{
+        match *self {{",
+            name = enum_.name,
+        )?;
+        for member in &members {
+            deprecated_feature_condition_no_doc(w, env, member.deprecated_version, false, 3)?;
+            version_condition_no_doc(w, env, member.version, false, 3)?;
+            writeln!(
+                w,
+                "\t\t\t{}::{} => serializer.serialize_str(\"{}\"),",
+                enum_.name, member.name, member.nick
+            )?;
+        }
+        writeln!(
+            w,
+            "            // Unknown values are serialized as their number
+            _ => serializer.serialize_i32(self.to_glib()),
+        }}
+    }}
+}}
+"
+        )?;
+        deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
+        version_condition(w, env, enum_.version, false, 0)?;
+        writeln!(
+            w,
+            "\
+#[cfg(feature = \"serde\")]
+impl<'de> serde::Deserialize<'de> for {name} {{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Repr {{
+            Nick(String),
+            Unknown(i32),
+        }}
+        match <Repr as serde::Deserialize>::deserialize(deserializer)? {{
+            Repr::Nick(nick) => match nick.as_str() {{",
+            name = enum_.name,
+        )?;
+        for member in &members {
+            deprecated_feature_condition_no_doc(w, env, member.deprecated_version, false, 4)?;
+            version_condition_no_doc(w, env, member.version, false, 4)?;
+            writeln!(
+                w,
+                "\t\t\t\t\"{}\" => Ok({}::{}),",
+                member.nick, enum_.name, member.name
+            )?;
+        }
+        writeln!(
+            w,
+            "                _ => Err(serde::de::Error::custom(format!(
+                    \"Invalid {name} value: {{}}\",
+                    nick
+                ))),
+            }},
+            Repr::Unknown(value) => Ok(unsafe {{ from_glib(value) }}),
+        }}
+    }}
+}}
+",
+            name = enum_.name,
+        )?;
+    }
+
     // Generate ToGlib trait implementation.
+    deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
     version_condition(w, env, enum_.version, false, 0)?;
     writeln!(
         w,
@@ -192,6 +365,7 @@ impl ToGlib for {name} {{
         ffi_name = enum_.c_type
     )?;
     for member in &members {
+        deprecated_feature_condition_no_doc(w, env, member.deprecated_version, false, 3)?;
         version_condition_no_doc(w, env, member.version, false, 3)?;
         writeln!(
             w,
@@ -216,6 +390,7 @@ impl ToGlib for {name} {{
     };
 
     // Generate FromGlib trait implementation.
+    deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
     version_condition(w, env, enum_.version, false, 0)?;
     writeln!(
         w,
@@ -229,6 +404,7 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
         assert = assert
     )?;
     for member in &members {
+        deprecated_feature_condition_no_doc(w, env, member.deprecated_version, false, 3)?;
         version_condition_no_doc(w, env, member.version, false, 3)?;
         writeln!(
             w,
@@ -250,6 +426,7 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
     if let Some(ref domain) = enum_.error_domain {
         let has_failed_member = members.iter().any(|m| m.name == "Failed");
 
+        deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
         version_condition(w, env, enum_.version, false, 0)?;
         writeln!(
             w,
@@ -296,6 +473,7 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
         )?;
 
         for member in &members {
+            deprecated_feature_condition_no_doc(w, env, member.deprecated_version, false, 3)?;
             version_condition_no_doc(w, env, member.version, false, 3)?;
             writeln!(
                 w,
@@ -327,6 +505,7 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
             .max()
             .flatten();
 
+        deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
         version_condition(w, env, version, false, 0)?;
         writeln!(
             w,
@@ -341,6 +520,7 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
         )?;
         writeln!(w)?;
 
+        deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
         version_condition(w, env, version, false, 0)?;
         writeln!(
             w,
@@ -354,6 +534,7 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
         )?;
         writeln!(w)?;
 
+        deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
         version_condition(w, env, version, false, 0)?;
         writeln!(
             w,
@@ -368,6 +549,7 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
         )?;
         writeln!(w)?;
 
+        deprecated_feature_condition(w, env, enum_.deprecated_version, false, 0)?;
         version_condition(w, env, version, false, 0)?;
         writeln!(
             w,
@@ -385,3 +567,100 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::env::tests::env;
+    use std::fs;
+
+    const COLOR: &str = r#"
+    <enumeration name="Color" c:type="TestColor">
+      <member name="red" value="0" c:identifier="TEST_COLOR_RED" glib:nick="red"/>
+      <member name="dark_blue" value="1" c:identifier="TEST_COLOR_DARK_BLUE" glib:nick="dark-blue"/>
+    </enumeration>
+    <enumeration name="Shape" c:type="TestShape">
+      <member name="round" value="0" c:identifier="TEST_SHAPE_ROUND" glib:nick="round"/>
+      <function name="from_name" c:identifier="test_shape_from_name">
+        <return-value transfer-ownership="none"><type name="Shape" c:type="TestShape"/></return-value>
+        <parameters>
+          <parameter name="name" transfer-ownership="none"><type name="utf8" c:type="const gchar*"/></parameter>
+        </parameters>
+      </function>
+    </enumeration>
+"#;
+
+    const CONFIG: &str = r#"
+[[object]]
+name = "Test.Color"
+status = "generate"
+
+[[object]]
+name = "Test.Shape"
+status = "generate"
+"#;
+
+    #[test]
+    fn name_tables_and_from_str() {
+        let code = env(COLOR, CONFIG).generate("enums.rs");
+        assert!(code.contains("use std::str;"));
+        assert!(code.contains("Color::DarkBlue => Some(\"TEST_COLOR_DARK_BLUE\"),"));
+        assert!(code.contains("\"dark-blue\" => Some(Color::DarkBlue),"));
+        assert!(code.contains("impl str::FromStr for Color {"));
+        // `Shape::from_name` is generated from the C function instead
+        assert!(!code.contains("impl str::FromStr for Shape {"));
+        assert!(!code.contains("Shape::Round => Some(\"round\"),"));
+    }
+
+    #[test]
+    fn name_tables_opt_out() {
+        let config = CONFIG.replace(
+            "name = \"Test.Color\"\n",
+            "name = \"Test.Color\"\ngenerate_from_str = false\n",
+        );
+        let code = env(COLOR, &config).generate("enums.rs");
+        assert!(!code.contains("use std::str;"));
+        assert!(!code.contains("pub fn from_nick("));
+        assert!(!code.contains("impl str::FromStr"));
+    }
+
+    #[test]
+    fn serde_matches_nicks_first() {
+        let config = CONFIG.replace(
+            "name = \"Test.Color\"\n",
+            "name = \"Test.Color\"\ngenerate_from_str = false\nserde = true\n",
+        );
+        let code = env(COLOR, &config).generate("enums.rs");
+        assert!(code.contains("impl serde::Serialize for Color {"));
+        assert!(code.contains(
+            "            Color::DarkBlue => serializer.serialize_str(\"dark-blue\"),\n            // Unknown values are serialized as their number\n            _ => serializer.serialize_i32(self.to_glib()),"
+        ));
+        assert!(code.contains("impl<'de> serde::Deserialize<'de> for Color {"));
+        assert!(code.contains(
+            "            Repr::Nick(nick) => match nick.as_str() {\n                \"red\" => Ok(Color::Red),\n                \"dark-blue\" => Ok(Color::DarkBlue),"
+        ));
+        assert!(
+            code.contains("            Repr::Unknown(value) => Ok(unsafe { from_glib(value) }),")
+        );
+    }
+
+    #[test]
+    fn serde_dependency_declared_in_cargo_toml() {
+        let config = CONFIG.replace(
+            "name = \"Test.Color\"\n",
+            "name = \"Test.Color\"\nserde = true\n",
+        );
+        let t = env(COLOR, &config);
+        let path = t.env.config.target_path.join("Cargo.toml");
+        fs::create_dir_all(&t.env.config.target_path).unwrap();
+        let manifest = "[package]\nname = \"test\"\n\n[dependencies]\nlibc = \"0.2\"\n";
+        fs::write(&path, manifest).unwrap();
+        t.generate("enums.rs");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!(
+                "{}serde = {{ version = \"1\", optional = true, features = [\"derive\"] }}\n",
+                manifest
+            )
+        );
+    }
+}
@@ -3,7 +3,9 @@ use crate::{
     analysis::flags::Info,
     analysis::special_functions::Type,
     codegen::general::{
-        self, cfg_deprecated, derives, version_condition, version_condition_string,
+        self, cfg_deprecated, deprecated_feature_condition, deprecated_feature_condition_no_doc,
+        deprecated_feature_condition_string, derives, version_condition, version_condition_no_doc,
+        version_condition_string,
     },
     config::gobjects::GObject,
     env::Env,
@@ -11,6 +13,7 @@ use crate::{
     library::*,
     nameutil::{bitfield_member_name, use_glib_type},
     traits::*,
+    version::Version,
 };
 use std::{
     io::{prelude::*, Result},
@@ -33,6 +36,11 @@ pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
             let config = &env.config.objects[&flags_analysis.full_name];
             let flags = flags_analysis.type_(&env.library);
 
+            if let Some(cfg) =
+                deprecated_feature_condition_string(env, flags.deprecated_version, false, 0)
+            {
+                mod_rs.push(cfg);
+            }
             if let Some(cfg) = version_condition_string(env, flags.version, false, 0) {
                 mod_rs.push(cfg);
             }
@@ -54,6 +62,7 @@ fn generate_flags(
 ) -> Result<()> {
     let sys_crate_name = env.main_sys_crate_name();
     cfg_deprecated(w, env, flags.deprecated_version, false, 0)?;
+    deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
     version_condition(w, env, flags.version, false, 0)?;
     writeln!(w, "bitflags! {{")?;
     if config.must_use {
@@ -64,7 +73,14 @@ fn generate_flags(
         derives(w, &d, 1)?;
     }
 
-    writeln!(w, "    pub struct {}: u32 {{", flags.name)?;
+    struct Member<'a> {
+        name: String,
+        member: &'a crate::library::Member,
+        version: Option<Version>,
+        deprecated_version: Option<Version>,
+    }
+
+    let mut members = Vec::new();
     for member in &flags.members {
         let member_config = config.members.matched(&member.name);
         let generate = member_config.iter().all(|m| m.status.need_generate());
@@ -72,16 +88,26 @@ fn generate_flags(
             continue;
         }
 
-        let name = bitfield_member_name(&member.name);
-        let val: i64 = member.value.parse().unwrap();
         let deprecated_version = member_config
             .iter()
             .filter_map(|m| m.deprecated_version)
             .next();
         let version = member_config.iter().filter_map(|m| m.version).next();
-        cfg_deprecated(w, env, deprecated_version, false, 2)?;
-        version_condition(w, env, version, false, 2)?;
-        writeln!(w, "\t\tconst {} = {};", name, val as u32)?;
+        members.push(Member {
+            name: bitfield_member_name(&member.name),
+            member,
+            version,
+            deprecated_version,
+        });
+    }
+
+    writeln!(w, "    pub struct {}: u32 {{", flags.name)?;
+    for member in &members {
+        let val: i64 = member.member.value.parse().unwrap();
+        cfg_deprecated(w, env, member.deprecated_version, false, 2)?;
+        deprecated_feature_condition(w, env, member.deprecated_version, false, 2)?;
+        version_condition(w, env, member.version, false, 2)?;
+        writeln!(w, "\t\tconst {} = {};", member.name, val as u32)?;
     }
 
     writeln!(
@@ -98,6 +124,7 @@ fn generate_flags(
 
     if !functions.is_empty() {
         writeln!(w)?;
+        deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
         version_condition(w, env, flags.version, false, 0)?;
         write!(w, "impl {} {{", analysis.name)?;
         for func_analysis in functions {
@@ -127,6 +154,7 @@ fn generate_flags(
 
     if config.generate_display_trait && !analysis.specials.has_trait(Type::Display) {
         // Generate Display trait implementation.
+        deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
         version_condition(w, env, flags.version, false, 0)?;
         writeln!(
             w,
@@ -139,6 +167,162 @@ fn generate_flags(
         )?;
     }
 
+    // Generate the tables of the C names and nicks of the single flags, used by
+    // `FromStr` to parse `a|b` strings.
+    let tables = [("name", "C name"), ("nick", "nick")];
+    if analysis.generate_tables {
+        deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
+        version_condition(w, env, flags.version, false, 0)?;
+        writeln!(w, "impl {} {{", flags.name)?;
+        for (i, &(field, doc)) in tables.iter().enumerate() {
+            if i > 0 {
+                writeln!(w)?;
+            }
+            writeln!(
+                w,
+                "    /// Returns the flag with the given {doc}.
+    pub fn from_{field}({field}: &str) -> Option<Self> {{
+        match {field} {{",
+                doc = doc,
+                field = field
+            )?;
+            for member in &members {
+                deprecated_feature_condition_no_doc(w, env, member.deprecated_version, false, 3)?;
+                version_condition_no_doc(w, env, member.version, false, 3)?;
+                let value = if field == "name" {
+                    &member.member.c_identifier
+                } else {
+                    &member.member.nick
+                };
+                writeln!(
+                    w,
+                    "\t\t\t\"{}\" => Some({}::{}),",
+                    value, flags.name, member.name
+                )?;
+            }
+            writeln!(
+                w,
+                "            _ => None,
+        }}
+    }}"
+            )?;
+        }
+        writeln!(w, "}}")?;
+        writeln!(w)?;
+    }
+
+    if analysis.generate_tables && !analysis.specials.has_trait(Type::FromStr) {
+        deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
+        version_condition(w, env, flags.version, false, 0)?;
+        writeln!(
+            w,
+            "\
+impl str::FromStr for {name} {{
+    type Err = {bool_error};
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {{
+        let mut flags = Self::empty();
+        for flag in s.split('|').map(|flag| flag.trim()).filter(|flag| !flag.is_empty()) {{
+            flags |= Self::from_nick(flag)
+                .or_else(|| Self::from_name(flag))
+                .ok_or_else(|| {bool_error_macro}(\"Invalid {name} value: {{}}\", flag))?;
+        }}
+        Ok(flags)
+    }}
+}}
+",
+            name = flags.name,
+            bool_error = use_glib_type(env, "BoolError"),
+            bool_error_macro = use_glib_type(env, "bool_error!"),
+        )?;
+    }
+
+    if env.config.generate_serde(&analysis.full_name) {
+        deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
+        version_condition(w, env, flags.version, false, 0)?;
+        writeln!(
+            w,
+            "\
+#[cfg(feature = \"serde\")]
+impl serde::Serialize for {name} {{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{
+        let mut nicks = Vec::new();",
+            name = flags.name,
+        )?;
+        for member in &members {
+            if member.member.value == "0" {
+                continue;
+            }
+            deprecated_feature_condition_no_doc(w, env, member.deprecated_version, false, 2)?;
+            version_condition_no_doc(w, env, member.version, false, 2)?;
+            writeln!(
+                w,
+                "\t\tif self.contains({}::{}) {{\n\t\t\tnicks.push(\"{}\");\n\t\t}}",
+                flags.name, member.name, member.member.nick
+            )?;
+        }
+        writeln!(
+            w,
+            "        let mut s = nicks.join(\"|\");
+        // Bits without a flag are serialized as their number
+        let unknown = self.bits() & !Self::all().bits();
+        if unknown != 0 {{
+            if !s.is_empty() {{
+                s.push('|');
+            }}
+            s.push_str(&unknown.to_string());
+        }}
+        serializer.serialize_str(&s)
+    }}
+}}
+"
+        )?;
+        deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
+        version_condition(w, env, flags.version, false, 0)?;
+        writeln!(
+            w,
+            "\
+#[cfg(feature = \"serde\")]
+impl<'de> serde::Deserialize<'de> for {name} {{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{
+        let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+        let mut flags = Self::empty();
+        for flag in s.split('|').map(|flag| flag.trim()).filter(|flag| !flag.is_empty()) {{
+            flags |= match flag {{",
+            name = flags.name,
+        )?;
+        for member in &members {
+            deprecated_feature_condition_no_doc(w, env, member.deprecated_version, false, 4)?;
+            version_condition_no_doc(w, env, member.version, false, 4)?;
+            writeln!(
+                w,
+                "\t\t\t\t\"{}\" => {}::{},",
+                member.member.nick, flags.name, member.name
+            )?;
+        }
+        writeln!(
+            w,
+            "                // Bits without a flag are serialized as their number
+                _ => match flag.parse::<u32>() {{
+                    Ok(bits) => unsafe {{ Self::from_bits_unchecked(bits) }},
+                    Err(_) => {{
+                        return Err(serde::de::Error::custom(format!(
+                            \"Invalid {name} value: {{}}\",
+                            flag
+                        )))
+                    }}
+                }},
+            }};
+        }}
+        Ok(flags)
+    }}
+}}
+",
+            name = flags.name,
+        )?;
+    }
+
+    deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
     version_condition(w, env, flags.version, false, 0)?;
     writeln!(
         w,
@@ -162,6 +346,7 @@ impl ToGlib for {name} {{
         ""
     };
 
+    deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
     version_condition(w, env, flags.version, false, 0)?;
     writeln!(
         w,
@@ -185,6 +370,7 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
             .max()
             .flatten();
 
+        deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
         version_condition(w, env, version, false, 0)?;
         writeln!(
             w,
@@ -199,6 +385,7 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
         )?;
         writeln!(w)?;
 
+        deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
         version_condition(w, env, version, false, 0)?;
         writeln!(
             w,
@@ -212,6 +399,7 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
         )?;
         writeln!(w)?;
 
+        deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
         version_condition(w, env, version, false, 0)?;
         writeln!(
             w,
@@ -226,6 +414,7 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
         )?;
         writeln!(w)?;
 
+        deprecated_feature_condition(w, env, flags.deprecated_version, false, 0)?;
         version_condition(w, env, version, false, 0)?;
         writeln!(
             w,
@@ -244,3 +433,57 @@ impl FromGlib<{sys_crate_name}::{ffi_name}> for {name} {{
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::env::tests::env;
+
+    const ACCESS: &str = r#"
+    <bitfield name="Access" c:type="TestAccess">
+      <member name="read" value="1" c:identifier="TEST_ACCESS_READ" glib:nick="read"/>
+      <member name="write" value="2" c:identifier="TEST_ACCESS_WRITE" glib:nick="write"/>
+    </bitfield>
+"#;
+
+    #[test]
+    fn name_tables_and_from_str() {
+        let code = env(
+            ACCESS,
+            "[[object]]\nname = \"Test.Access\"\nstatus = \"generate\"\n",
+        )
+        .generate("flags.rs");
+        assert!(code.contains("use std::str;"));
+        assert!(code.contains("\"TEST_ACCESS_WRITE\" => Some(Access::WRITE),"));
+        assert!(code.contains("\"write\" => Some(Access::WRITE),"));
+        assert!(code.contains("impl str::FromStr for Access {"));
+        assert!(code.contains("for flag in s.split('|')"));
+    }
+
+    #[test]
+    fn serde_keeps_unknown_bits() {
+        let code = env(
+            ACCESS,
+            "[[object]]\nname = \"Test.Access\"\nstatus = \"generate\"\nserde = true\n",
+        )
+        .generate("flags.rs");
+        assert!(code.contains("impl serde::Serialize for Access {"));
+        assert!(code.contains("let unknown = self.bits() & !Self::all().bits();"));
+        assert!(code.contains("s.push_str(&unknown.to_string());"));
+        assert!(code.contains("impl<'de> serde::Deserialize<'de> for Access {"));
+        assert!(code.contains("Ok(bits) => unsafe { Self::from_bits_unchecked(bits) },"));
+    }
+
+    #[test]
+    fn serde_without_name_tables() {
+        let code = env(
+            ACCESS,
+            "[[object]]\nname = \"Test.Access\"\nstatus = \"generate\"\nserde = true\ngenerate_from_str = false\n",
+        )
+        .generate("flags.rs");
+        assert!(!code.contains("pub fn from_nick("));
+        assert!(code.contains("impl serde::Serialize for Access {"));
+        assert!(code.contains(
+            "            flags |= match flag {\n                \"read\" => Access::READ,\n                \"write\" => Access::WRITE,\n                // Bits without a flag are serialized as their number\n                _ => match flag.parse::<u32>() {"
+        ));
+    }
+}
@@ -1,8 +1,8 @@
 use super::{
     function_body_chunk,
     general::{
-        cfg_condition, cfg_deprecated, doc_alias, doc_hidden, not_version_condition,
-        version_condition,
+        cfg_condition, cfg_deprecated, deprecated_feature_condition, doc_alias, doc_hidden,
+        not_version_condition, version_condition,
     },
     parameter::ToParameter,
     return_value::{out_parameters_as_return, ToReturnValue},
@@ -77,13 +77,32 @@ pub fn generate(
 
     writeln!(w)?;
     if !in_trait || only_declaration {
+        for trampoline in analysis.callbacks.iter().filter(|c| c.scope.is_forever()) {
+            writeln!(
+                w,
+                "{}{}/// `{}` is called until the end of the program, it's leaked and never freed.",
+                tabs(indent),
+                comment_prefix,
+                trampoline.name
+            )?;
+        }
         cfg_deprecated(w, env, analysis.deprecated_version, commented, indent)?;
     }
+    deprecated_feature_condition(
+        w,
+        env,
+        analysis.deprecated_feature_version,
+        commented,
+        indent,
+    )?;
     cfg_condition(w, &analysis.cfg_condition, commented, indent)?;
     version_condition(w, env, analysis.version, commented, indent)?;
     not_version_condition(w, analysis.not_version, commented, indent)?;
     doc_hidden(w, analysis.doc_hidden, comment_prefix, indent)?;
     if !in_trait || only_declaration {
+        if let Some(ref varargs) = analysis.varargs {
+            doc_alias(w, varargs, comment_prefix, indent)?;
+        }
         doc_alias(w, &analysis.glib_name, comment_prefix, indent)?;
     }
     writeln!(
@@ -103,41 +122,103 @@ pub fn generate(
         }
     }
 
-    if analysis.async_future.is_some() {
-        let declaration = declaration_futures(env, analysis);
-        let suffix = if only_declaration { ";" } else { " {" };
-
-        writeln!(w)?;
-        if !in_trait || only_declaration {
-            cfg_deprecated(w, env, analysis.deprecated_version, commented, indent)?;
-        }
-
-        writeln!(w, "{}{}", tabs(indent), comment_prefix)?;
-        cfg_condition(w, &analysis.cfg_condition, commented, indent)?;
-        version_condition(w, env, analysis.version, commented, indent)?;
-        not_version_condition(w, analysis.not_version, commented, indent)?;
-        doc_hidden(w, analysis.doc_hidden, comment_prefix, indent)?;
-        writeln!(
+    if let Some(ref async_future) = analysis.async_future {
+        let body = if only_declaration {
+            None
+        } else {
+            Some(body_chunk_futures(env, analysis).unwrap())
+        };
+        generate_async_variant(
             w,
-            "{}{}{}{}{}",
-            tabs(indent),
+            env,
+            analysis,
+            &declaration_futures(env, analysis),
+            body,
+            in_trait,
+            only_declaration,
+            commented,
             comment_prefix,
             pub_prefix,
-            declaration,
-            suffix
+            indent,
         )?;
 
-        if !only_declaration {
-            let body = body_chunk_futures(env, analysis).unwrap();
-            for s in body.lines() {
-                if !s.is_empty() {
-                    writeln!(w, "{}{}{}", tabs(indent + 1), comment_prefix, s)?;
-                } else {
-                    writeln!(w)?;
-                }
+        if async_future.stream.is_some() {
+            let body = if only_declaration {
+                None
+            } else {
+                Some(body_chunk_stream(analysis).unwrap())
+            };
+            generate_async_variant(
+                w,
+                env,
+                analysis,
+                &declaration_stream(env, analysis),
+                body,
+                in_trait,
+                only_declaration,
+                commented,
+                comment_prefix,
+                pub_prefix,
+                indent,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a function built on the async function, like its `_future` variant.
+fn generate_async_variant(
+    w: &mut dyn Write,
+    env: &Env,
+    analysis: &analysis::functions::Info,
+    declaration: &str,
+    body: Option<String>,
+    in_trait: bool,
+    only_declaration: bool,
+    commented: bool,
+    comment_prefix: &str,
+    pub_prefix: &str,
+    indent: usize,
+) -> Result<()> {
+    let suffix = if only_declaration { ";" } else { " {" };
+
+    writeln!(w)?;
+    if !in_trait || only_declaration {
+        cfg_deprecated(w, env, analysis.deprecated_version, commented, indent)?;
+    }
+
+    writeln!(w, "{}{}", tabs(indent), comment_prefix)?;
+    deprecated_feature_condition(
+        w,
+        env,
+        analysis.deprecated_feature_version,
+        commented,
+        indent,
+    )?;
+    cfg_condition(w, &analysis.cfg_condition, commented, indent)?;
+    version_condition(w, env, analysis.version, commented, indent)?;
+    not_version_condition(w, analysis.not_version, commented, indent)?;
+    doc_hidden(w, analysis.doc_hidden, comment_prefix, indent)?;
+    writeln!(
+        w,
+        "{}{}{}{}{}",
+        tabs(indent),
+        comment_prefix,
+        pub_prefix,
+        declaration,
+        suffix
+    )?;
+
+    if let Some(body) = body {
+        for s in body.lines() {
+            if !s.is_empty() {
+                writeln!(w, "{}{}{}", tabs(indent + 1), comment_prefix, s)?;
+            } else {
+                writeln!(w)?;
             }
-            writeln!(w, "{}{}}}", tabs(indent), comment_prefix)?;
         }
+        writeln!(w, "{}{}}}", tabs(indent), comment_prefix)?;
     }
 
     Ok(())
@@ -165,7 +246,7 @@ pub fn declaration(env: &Env, analysis: &analysis::functions::Info) -> String {
             param_str.push_str(", ")
         }
         let c_par = &analysis.parameters.c_parameters[par.ind_c];
-        let s = c_par.to_parameter(env, &analysis.bounds);
+        let s = c_par.to_parameter(env, &analysis.bounds, false);
         param_str.push_str(&s);
     }
 
@@ -186,6 +267,29 @@ pub fn declaration_futures(env: &Env, analysis: &analysis::functions::Info) -> S
         async_future.success_parameters, async_future.error_parameters
     );
 
+    async_variant_declaration(env, analysis, &async_future.name, &return_str)
+}
+
+pub fn declaration_stream(env: &Env, analysis: &analysis::functions::Info) -> String {
+    let async_future = analysis.async_future.as_ref().unwrap();
+    let stream = async_future.stream.as_ref().unwrap();
+
+    let return_str = format!(
+        " -> Pin<Box_<dyn futures_core::Stream<Item = Result<{}, {}>> + 'static>>",
+        stream.item, async_future.error_parameters
+    );
+
+    async_variant_declaration(env, analysis, &stream.name, &return_str)
+}
+
+/// Declaration of a function taking the parameters of the async function, except for its
+/// callback and cancellable.
+fn async_variant_declaration(
+    env: &Env,
+    analysis: &analysis::functions::Info,
+    name: &str,
+    return_str: &str,
+) -> String {
     let mut param_str = String::with_capacity(100);
 
     let mut skipped = 0;
@@ -195,7 +299,7 @@ pub fn declaration_futures(env: &Env, analysis: &analysis::functions::Info) -> S
 
         if c_par.name == "callback" || c_par.name == "cancellable" {
             skipped += 1;
-            if let Some((t, _)) = analysis.bounds.get_parameter_alias_info(&c_par.name) {
+            if let Some((Some(t), _)) = analysis.bounds.get_parameter_alias_info(&c_par.name) {
                 skipped_bounds.push(t);
                 if let Some(p) = analysis.bounds.get_base_alias(t) {
                     skipped_bounds.push(p);
@@ -208,26 +312,27 @@ pub fn declaration_futures(env: &Env, analysis: &analysis::functions::Info) -> S
             param_str.push_str(", ")
         }
 
-        let s = c_par.to_parameter(env, &analysis.bounds);
+        let s = c_par.to_parameter(env, &analysis.bounds, true);
         param_str.push_str(&s);
     }
 
     let (bounds, _) = bounds(&analysis.bounds, skipped_bounds.as_ref(), true, false);
 
-    format!(
-        "fn {}{}({}){}",
-        async_future.name, bounds, param_str, return_str,
-    )
+    format!("fn {}{}({}){}", name, bounds, param_str, return_str)
+}
+
+pub fn bound_to_string(alias: char, bound: &Bound, r#async: bool) -> String {
+    format!("{}: {}", alias, bound_traits(bound, r#async))
 }
 
-pub fn bound_to_string(bound: &Bound, r#async: bool) -> String {
+/// The traits a bound requires, like `IsA<Widget>`
+pub fn bound_traits(bound: &Bound, r#async: bool) -> String {
     use crate::analysis::bounds::BoundType::*;
 
     match bound.bound_type {
-        NoWrapper => format!("{}: {}", bound.alias, bound.type_str),
+        NoWrapper => bound.type_str.clone(),
         IsA(Some(lifetime)) => format!(
-            "{}: IsA<{}> + {}",
-            bound.alias,
+            "IsA<{}> + {}",
             bound.type_str,
             if r#async {
                 "Clone + 'static".into()
@@ -236,17 +341,16 @@ pub fn bound_to_string(bound: &Bound, r#async: bool) -> String {
             }
         ),
         IsA(None) => format!(
-            "{}: IsA<{}>{}",
-            bound.alias,
+            "IsA<{}>{}",
             bound.type_str,
             if r#async { " + Clone + 'static" } else { "" }
         ),
         // This case should normally never happened
         AsRef(Some(_ /*lifetime*/)) => {
             unreachable!();
-            // format!("{}: AsRef<{}> + '{}", bound.alias, bound.type_str, lifetime)
+            // format!("AsRef<{}> + '{}", bound.type_str, lifetime)
         }
-        AsRef(None) => format!("{}: AsRef<{}>", bound.alias, bound.type_str),
+        AsRef(None) => format!("AsRef<{}>", bound.type_str),
     }
 }
 
@@ -264,25 +368,35 @@ pub fn bounds(
 
     let skip_lifetimes = bounds
         .iter()
-        .filter(|bound| skip.contains(&bound.alias))
+        .filter(|bound| matches!(bound.alias, Some(alias) if skip.contains(&alias)))
         .filter_map(|bound| match bound.bound_type {
             IsA(Some(lifetime)) | AsRef(Some(lifetime)) => Some(lifetime),
             _ => None,
         })
         .collect::<Vec<_>>();
 
+    // `impl Trait` bounds are part of the parameter's type
+    let named = bounds
+        .iter()
+        .filter_map(|bound| match bound.alias {
+            Some(alias)
+                if !skip.contains(&alias)
+                    && (!filter_callback_modified || !bound.callback_modified) =>
+            {
+                Some((alias, bound))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
     let strs: Vec<String> = bounds
         .iter_lifetimes()
         .filter(|s| !skip_lifetimes.contains(s))
         .map(|s| format!("'{}", s))
         .chain(
-            bounds
+            named
                 .iter()
-                .filter(|bound| {
-                    !skip.contains(&bound.alias)
-                        && (!filter_callback_modified || !bound.callback_modified)
-                })
-                .map(|b| bound_to_string(b, r#async)),
+                .map(|&(alias, bound)| bound_to_string(alias, bound, r#async)),
         )
         .collect();
 
@@ -293,15 +407,7 @@ pub fn bounds(
             .iter_lifetimes()
             .filter(|s| !skip_lifetimes.contains(s))
             .map(|s| format!("'{}", s))
-            .chain(
-                bounds
-                    .iter()
-                    .filter(|bound| {
-                        !skip.contains(&bound.alias)
-                            && (!filter_callback_modified || !bound.callback_modified)
-                    })
-                    .map(|b| b.alias.to_string()),
-            )
+            .chain(named.iter().map(|(alias, _)| alias.to_string()))
             .collect::<Vec<_>>();
         (format!("<{}>", strs.join(", ")), bounds)
     }
@@ -343,6 +449,9 @@ pub fn body_chunk(env: &Env, analysis: &analysis::functions::Info) -> Chunk {
             builder.destroy(trampoline);
         }
     }
+    for closure in &analysis.closures {
+        builder.closure(closure);
+    }
 
     for par in &analysis.parameters.c_parameters {
         if outs_as_return && analysis.outs.iter().any(|p| p.name == par.name) {
@@ -383,10 +492,16 @@ pub fn body_chunk_futures(
         )?;
     }
     let skip = if async_future.is_method { 1 } else { 0 };
+    // The progress closure is moved as is to the async function
+    let progress = analysis
+        .trampoline
+        .as_ref()
+        .and_then(|trampoline| trampoline.progress.as_ref())
+        .map(|progress| progress.name.as_str());
 
     // Skip the instance parameter
     for par in analysis.parameters.rust_parameters.iter().skip(skip) {
-        if par.name == "cancellable" || par.name == "callback" {
+        if par.name == "cancellable" || par.name == "callback" || Some(&*par.name) == progress {
             continue;
         }
 
@@ -444,6 +559,8 @@ pub fn body_chunk_futures(
             writeln!(body, "\t\tSome(&cancellable),")?;
         } else if par.name == "callback" {
             continue;
+        } else if Some(&*par.name) == progress {
+            writeln!(body, "\t\t{},", par.name)?;
         } else {
             let c_par = &analysis.parameters.c_parameters[par.ind_c];
 
@@ -471,3 +588,314 @@ pub fn body_chunk_futures(
 
     Ok(body)
 }
+
+pub fn body_chunk_stream(analysis: &analysis::functions::Info) -> StdResult<String, fmt::Error> {
+    use crate::analysis::functions::StreamEnd;
+    use std::fmt::Write;
+
+    let async_future = analysis.async_future.as_ref().unwrap();
+    let stream = async_future.stream.as_ref().unwrap();
+
+    let mut body = String::new();
+
+    // The instance is kept in the state of the stream, until it ends
+    let (init, state, next_state, target) = if async_future.is_method {
+        ("Some(self.clone())", "let obj = obj?;", "Some(obj)", "obj.")
+    } else if analysis.type_name.is_ok() {
+        ("Some(())", "obj?;", "Some(())", "Self::")
+    } else {
+        ("Some(())", "obj?;", "Some(())", "")
+    };
+    let skip = if async_future.is_method { 1 } else { 0 };
+    let args = analysis
+        .parameters
+        .rust_parameters
+        .iter()
+        .skip(skip)
+        .filter(|par| par.name != "cancellable" && par.name != "callback")
+        .map(|par| par.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let item = if stream.convert {
+        "item.into()"
+    } else {
+        "item"
+    };
+
+    writeln!(
+        body,
+        "Box_::pin(futures_util::stream::unfold({}, move |obj| async move {{",
+        init
+    )?;
+    writeln!(body, "\t{}", state)?;
+    writeln!(
+        body,
+        "\tmatch {}{}({}).await {{",
+        target, async_future.name, args
+    )?;
+    match stream.end {
+        StreamEnd::None => {
+            writeln!(body, "\t\tOk(None) => None,")?;
+            writeln!(
+                body,
+                "\t\tOk(Some(item)) => Some((Ok({}), {})),",
+                item, next_state
+            )?;
+        }
+        StreamEnd::Empty => {
+            writeln!(body, "\t\tOk(item) if item.is_empty() => None,")?;
+            writeln!(
+                body,
+                "\t\tOk(item) => Some((Ok({}), {})),",
+                item, next_state
+            )?;
+        }
+    }
+    writeln!(body, "\t\tErr(err) => Some((Err(err), None)),")?;
+    writeln!(body, "\t}}")?;
+    writeln!(body, "}}))")?;
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::env::tests::env;
+    use std::fs;
+
+    const WIDGET: &str = r#"
+    <callback name="WidgetChanged" c:type="TestWidgetChanged">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></parameter>
+        <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1" closure="1"><type name="gpointer" c:type="gpointer"/></parameter>
+      </parameters>
+    </callback>
+    <class name="Widget" c:symbol-prefix="widget" c:type="TestWidget" parent="GObject.Object" glib:type-name="TestWidget" glib:get-type="test_widget_get_type">
+      <method name="attach" c:identifier="test_widget_attach">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="target" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></parameter>
+          <parameter name="parent" transfer-ownership="none" nullable="1" allow-none="1"><type name="Widget" c:type="TestWidget*"/></parameter>
+        </parameters>
+      </method>
+      <method name="foreach" c:identifier="test_widget_foreach">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="func" transfer-ownership="none" scope="call" closure="1"><type name="WidgetChanged" c:type="TestWidgetChanged"/></parameter>
+          <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1"><type name="gpointer" c:type="gpointer"/></parameter>
+        </parameters>
+      </method>
+      <method name="move_to_async" c:identifier="test_widget_move_to_async">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="target" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></parameter>
+          <parameter name="cancellable" transfer-ownership="none" nullable="1" allow-none="1"><type name="Gio.Cancellable" c:type="GCancellable*"/></parameter>
+          <parameter name="callback" transfer-ownership="none" nullable="1" allow-none="1" scope="async" closure="3"><type name="Gio.AsyncReadyCallback" c:type="GAsyncReadyCallback"/></parameter>
+          <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1"><type name="gpointer" c:type="gpointer"/></parameter>
+        </parameters>
+      </method>
+      <method name="move_to_finish" c:identifier="test_widget_move_to_finish" throws="1">
+        <return-value transfer-ownership="none"><type name="gboolean" c:type="gboolean"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="res" transfer-ownership="none"><type name="Gio.AsyncResult" c:type="GAsyncResult*"/></parameter>
+        </parameters>
+      </method>
+      <method name="read_line" c:identifier="test_widget_read_line" throws="1">
+        <return-value transfer-ownership="full" nullable="1"><type name="utf8" c:type="gchar*"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="cancellable" transfer-ownership="none" nullable="1" allow-none="1"><type name="Gio.Cancellable" c:type="GCancellable*"/></parameter>
+        </parameters>
+      </method>
+      <method name="read_line_async" c:identifier="test_widget_read_line_async">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="cancellable" transfer-ownership="none" nullable="1" allow-none="1"><type name="Gio.Cancellable" c:type="GCancellable*"/></parameter>
+          <parameter name="callback" transfer-ownership="none" nullable="1" allow-none="1" scope="async" closure="2"><type name="Gio.AsyncReadyCallback" c:type="GAsyncReadyCallback"/></parameter>
+          <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1"><type name="gpointer" c:type="gpointer"/></parameter>
+        </parameters>
+      </method>
+      <method name="read_line_finish" c:identifier="test_widget_read_line_finish" throws="1">
+        <return-value transfer-ownership="full" nullable="1"><type name="utf8" c:type="gchar*"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="res" transfer-ownership="none"><type name="Gio.AsyncResult" c:type="GAsyncResult*"/></parameter>
+        </parameters>
+      </method>
+      <method name="read_chunk_async" c:identifier="test_widget_read_chunk_async">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="cancellable" transfer-ownership="none" nullable="1" allow-none="1"><type name="Gio.Cancellable" c:type="GCancellable*"/></parameter>
+          <parameter name="callback" transfer-ownership="none" nullable="1" allow-none="1" scope="async" closure="2"><type name="Gio.AsyncReadyCallback" c:type="GAsyncReadyCallback"/></parameter>
+          <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1"><type name="gpointer" c:type="gpointer"/></parameter>
+        </parameters>
+      </method>
+      <method name="read_chunk_finish" c:identifier="test_widget_read_chunk_finish" throws="1">
+        <return-value transfer-ownership="full"><type name="utf8" c:type="gchar*"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="res" transfer-ownership="none"><type name="Gio.AsyncResult" c:type="GAsyncResult*"/></parameter>
+        </parameters>
+      </method>
+    </class>
+    <class name="Button" c:symbol-prefix="button" c:type="TestButton" parent="Widget" glib:type-name="TestButton" glib:get-type="test_button_get_type">
+    </class>"#;
+
+    const CONFIG: &str = r#"impl_trait_args = true
+
+[[object]]
+name = "Gio.Cancellable"
+status = "manual"
+
+[[object]]
+name = "Test.Widget"
+status = "generate"
+"#;
+
+    #[test]
+    fn impl_trait_args() {
+        let code = env(WIDGET, CONFIG).generate("widget.rs");
+        assert!(code.contains(
+            "fn attach(&self, target: &impl IsA<Widget>, parent: Option<&impl IsA<Widget>>);"
+        ));
+        assert!(code.contains(
+            "fn move_to_async<P: FnOnce(Result<(), glib::Error>) + Send + 'static>(&self, target: &impl IsA<Widget>, cancellable: Option<&gio::Cancellable>, callback: P);"
+        ));
+        // `&impl A + B` is ambiguous
+        assert!(code.contains(
+            "fn move_to_async_future(&self, target: &(impl IsA<Widget> + Clone + 'static))"
+        ));
+        // Callbacks are named by their trampolines
+        assert!(code.contains("fn foreach<P: FnMut(&Widget)>(&self, func: P);"));
+    }
+
+    /// Makes `Gio.Cancellable` non-final, so it is taken through a bound by default.
+    const CANCELLABLE_SUBCLASS: &str = r#"
+    <class name="TimedCancellable" c:symbol-prefix="timed_cancellable" c:type="TestTimedCancellable" parent="Gio.Cancellable" glib:type-name="TestTimedCancellable" glib:get-type="test_timed_cancellable_get_type">
+    </class>"#;
+
+    #[test]
+    fn concrete_cancellable() {
+        let namespace = format!("{}{}", WIDGET, CANCELLABLE_SUBCLASS);
+        let config = CONFIG.replace("impl_trait_args = true", "concrete_cancellable = true");
+        let code = env(&namespace, &config).generate("widget.rs");
+        // Sync
+        assert!(code.contains(
+            "fn read_line(&self, cancellable: Option<&gio::Cancellable>) -> Result<Option<glib::GString>, glib::Error> {"
+        ));
+        assert!(code.contains(
+            "ffi::test_widget_read_line(self.as_ref().to_glib_none().0, cancellable.to_glib_none().0, &mut error);"
+        ));
+        // Async
+        assert!(code.contains(
+            "fn read_line_async<P: FnOnce(Result<Option<glib::GString>, glib::Error>) + Send + 'static>(&self, cancellable: Option<&gio::Cancellable>, callback: P) {"
+        ));
+        assert!(code.contains(
+            "ffi::test_widget_read_line_async(self.as_ref().to_glib_none().0, cancellable.to_glib_none().0, Some(callback), Box_::into_raw(user_data) as *mut _);"
+        ));
+        // Future, passing its own cancellable to the async function
+        assert!(code.contains(
+            "            let cancellable = gio::Cancellable::new();\n            obj.read_line_async(\n                Some(&cancellable),"
+        ));
+        assert!(!code.contains("IsA<gio::Cancellable>"));
+
+        let config = CONFIG.replace("impl_trait_args = true", "");
+        let code = env(&namespace, &config).generate("widget.rs");
+        assert!(
+            code.contains("fn read_line<P: IsA<gio::Cancellable>>(&self, cancellable: Option<&P>)")
+        );
+        assert!(code.contains("cancellable.map(|p| p.as_ref()).to_glib_none().0"));
+    }
+
+    #[test]
+    fn named_type_parameters() {
+        let code = env(WIDGET, &CONFIG.replace("impl_trait_args = true", "")).generate("widget.rs");
+        assert!(code.contains(
+            "fn attach<P: IsA<Widget>, Q: IsA<Widget>>(&self, target: &P, parent: Option<&Q>);"
+        ));
+        assert!(code.contains(
+            "fn move_to_async_future<P: IsA<Widget> + Clone + 'static>(&self, target: &P)"
+        ));
+    }
+
+    const STREAM_CONFIG: &str = r#"
+[[object]]
+name = "Gio.Cancellable"
+status = "manual"
+
+[[object]]
+name = "Test.Widget"
+status = "generate"
+    [[object.function]]
+    name = "read_line_async"
+    stream = {}
+    [[object.function]]
+    name = "read_chunk_async"
+    stream = { item = "Chunk", end = "empty" }
+"#;
+
+    #[test]
+    fn nullable_stream() {
+        let code = env(WIDGET, STREAM_CONFIG).generate("widget.rs");
+        assert!(code.contains(
+            "fn read_line_stream(&self) -> Pin<Box_<dyn futures_core::Stream<Item = Result<glib::GString, glib::Error>> + 'static>> {"
+        ));
+        assert!(code.contains(
+            "Box_::pin(futures_util::stream::unfold(Some(self.clone()), move |obj| async move {"
+        ));
+        assert!(code.contains(
+            "match obj.read_line_async_future().await {\n                Ok(None) => None,\n                Ok(Some(item)) => Some((Ok(item), Some(obj))),\n                Err(err) => Some((Err(err), None)),"
+        ));
+    }
+
+    #[test]
+    fn empty_stream() {
+        let code = env(WIDGET, STREAM_CONFIG).generate("widget.rs");
+        assert!(code.contains(
+            "fn read_chunk_stream(&self) -> Pin<Box_<dyn futures_core::Stream<Item = Result<Chunk, glib::Error>> + 'static>> {"
+        ));
+        assert!(code.contains(
+            "match obj.read_chunk_async_future().await {\n                Ok(item) if item.is_empty() => None,\n                Ok(item) => Some((Ok(item.into()), Some(obj))),"
+        ));
+    }
+
+    #[test]
+    fn endless_stream_is_skipped() {
+        let config = STREAM_CONFIG.replace(", end = \"empty\"", "");
+        let code = env(WIDGET, &config).generate("widget.rs");
+        assert!(code.contains("fn read_line_stream("));
+        assert!(!code.contains("fn read_chunk_stream("));
+    }
+
+    #[test]
+    fn stream_dependencies_declared_in_cargo_toml() {
+        let t = env(WIDGET, STREAM_CONFIG);
+        let path = t.env.config.target_path.join("Cargo.toml");
+        fs::create_dir_all(&t.env.config.target_path).unwrap();
+        let manifest = "[package]\nname = \"test\"\n\n[dependencies]\n# Streams\nfutures-core = \"0.3.5\"\n\n[dev-dependencies]\n";
+        fs::write(&path, manifest).unwrap();
+        t.generate("widget.rs");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            manifest.replace("\"0.3.5\"\n", "\"0.3.5\"\nfutures-util = \"0.3\"\n")
+        );
+    }
+
+    #[test]
+    fn renamed_and_platform_dependencies_are_declared() {
+        let t = env(WIDGET, STREAM_CONFIG);
+        let path = t.env.config.target_path.join("Cargo.toml");
+        fs::create_dir_all(&t.env.config.target_path).unwrap();
+        let manifest = "[package]\nname = \"test\"\n\n[dependencies]\nfcore = { package = \"futures-core\", version = \"0.3\" }\n\n[target.'cfg(unix)'.dependencies]\nfutures-util = \"0.3\"\n";
+        fs::write(&path, manifest).unwrap();
+        t.generate("widget.rs");
+        assert_eq!(fs::read_to_string(&path).unwrap(), manifest);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     analysis::{
-        conversion_type::ConversionType,
+        conversion_type::{is_direct_fixed_array, ConversionType},
         function_parameters::{
             CParameter as AnalysisCParameter, Transformation, TransformationType,
         },
@@ -17,7 +17,10 @@ use crate::{
     library::{self, ParameterDirection, TypeId},
     nameutil::{is_gstring, use_gio_type, use_glib_if_needed, use_glib_type},
 };
-use std::collections::{hash_map::Entry, BTreeMap, HashMap};
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    mem,
+};
 
 #[derive(Clone, Debug)]
 enum Parameter {
@@ -55,6 +58,7 @@ pub struct Builder {
     async_trampoline: Option<AsyncTrampoline>,
     callbacks: Vec<Trampoline>,
     destroys: Vec<Trampoline>,
+    closures: Vec<Trampoline>,
     glib_name: String,
     parameters: Vec<Parameter>,
     transformations: Vec<Transformation>,
@@ -90,6 +94,10 @@ impl Builder {
         self.destroys.push(trampoline.clone());
         self
     }
+    pub fn closure(&mut self, closure: &Trampoline) -> &mut Builder {
+        self.closures.push(closure.clone());
+        self
+    }
     pub fn glib_name(&mut self, name: &str) -> &mut Builder {
         self.glib_name = name.into();
         self
@@ -151,43 +159,26 @@ impl Builder {
                     .iter()
                     .filter(|c| c.user_data_index == user_data_index)
                     .collect::<Vec<_>>();
+                let tuple = format!(
+                    "({})",
+                    calls
+                        .iter()
+                        .map(|c| c.bound_name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
                 group_by_user_data.insert(
                     user_data_index,
                     FuncParameter {
                         pos,
-                        full_type: if calls.len() > 1 {
-                            if calls.iter().all(|c| c.scope.is_call()) {
-                                Some((
-                                    format!(
-                                        "&({})",
-                                        calls
-                                            .iter()
-                                            .map(|c| format!("&{}", c.bound_name))
-                                            .collect::<Vec<_>>()
-                                            .join(", ")
-                                    ),
-                                    format!(
-                                        "&mut ({})",
-                                        calls
-                                            .iter()
-                                            .map(|c| format!("&mut {}", c.bound_name))
-                                            .collect::<Vec<_>>()
-                                            .join(", ")
-                                    ),
-                                ))
-                            } else {
-                                let s = format!(
-                                    "Box_<({})>",
-                                    calls
-                                        .iter()
-                                        .map(|c| format!("&{}", c.bound_name))
-                                        .collect::<Vec<_>>()
-                                        .join(", ")
-                                );
-                                Some((s.clone(), s))
-                            }
-                        } else {
+                        // The type of the shared user data, and the type it's read as in the
+                        // trampolines
+                        full_type: if calls.len() < 2 {
                             None
+                        } else if calls.iter().all(|c| c.scope.is_call()) {
+                            Some((tuple.clone(), format!("*mut {}", tuple)))
+                        } else {
+                            Some((format!("Box_<{}>", tuple), tuple))
                         },
                         callbacks: calls,
                     },
@@ -211,6 +202,7 @@ impl Builder {
         let mut chunks = Vec::new();
 
         self.add_in_array_lengths(&mut chunks);
+        self.add_closures(env, &mut chunks, &bounds, &bounds_names);
         self.add_assertion(&mut chunks);
 
         if !self.callbacks.is_empty() || !self.destroys.is_empty() {
@@ -266,36 +258,21 @@ impl Builder {
             } in group_by_user_data.values()
             {
                 if calls.len() > 1 {
+                    let data = calls
+                        .iter()
+                        .map(|c| format!("{}_data", c.name))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    // The trampolines mutate the tuple of call scoped callbacks through the
+                    // user data, so it's borrowed mutably for the call
+                    let all_call = calls.iter().all(|c| c.scope.is_call());
                     chunks.push(Chunk::Let {
                         name: format!("super_callback{}", pos),
-                        is_mut: false,
-                        value: Box::new(Chunk::Custom(if poses.is_empty() {
-                            format!(
-                                "Box_::new(Box_::new(({})))",
-                                calls
-                                    .iter()
-                                    .map(|c| format!("{}_data", c.name))
-                                    .collect::<Vec<_>>()
-                                    .join(", ")
-                            )
-                        } else if calls.iter().all(|c| c.scope.is_call()) {
-                            format!(
-                                "&({})",
-                                calls
-                                    .iter()
-                                    .map(|c| format!("{}_data", c.name))
-                                    .collect::<Vec<_>>()
-                                    .join(", ")
-                            )
+                        is_mut: all_call,
+                        value: Box::new(Chunk::Custom(if all_call {
+                            format!("({})", data)
                         } else {
-                            format!(
-                                "Box_::new(({}))",
-                                calls
-                                    .iter()
-                                    .map(|c| format!("{}_data", c.name))
-                                    .collect::<Vec<_>>()
-                                    .join(", ")
-                            )
+                            format!("Box_::new(({}))", data)
                         })),
                         type_: Some(Box::new(Chunk::Custom(
                             full_type.clone().map(|x| x.0).unwrap(),
@@ -319,7 +296,7 @@ impl Builder {
                 }
             }
         } else if let Some(ref trampoline) = self.async_trampoline {
-            self.add_async_trampoline(env, &mut chunks, trampoline);
+            self.add_async_trampoline(env, &mut chunks, trampoline, &bounds, &bounds_names);
         }
         chunks.push(unsafe_);
         Chunk::BlockHalf(chunks)
@@ -368,29 +345,55 @@ impl Builder {
         bounds_names: &str,
         is_destroy: bool,
     ) {
+        self.add_trampoline_func(env, chunks, trampoline, full_type, pos, bounds, is_destroy);
+        let bounds_str = if bounds_names.is_empty() {
+            String::new()
+        } else {
+            format!("::<{}>", bounds_names)
+        };
         if !is_destroy {
-            if full_type.is_none() {
-                if trampoline.scope.is_call() {
-                    chunks.push(Chunk::Custom(format!(
-                        "let {0}_data: {1} = {0};",
-                        trampoline.name, trampoline.bound_name
-                    )));
-                } else {
-                    chunks.push(Chunk::Custom(format!(
-                        "let {0}_data: Box_<{1}> = Box_::new({0});",
-                        trampoline.name, trampoline.bound_name
-                    )));
-                }
-            } else if trampoline.scope.is_call() {
+            if *trampoline.nullable {
                 chunks.push(Chunk::Custom(format!(
-                    "let {0}_data: &{1} = &{0};",
-                    trampoline.name, trampoline.bound_name
+                    "let {0} = if {0}_data.is_some() {{ Some({0}_func{1} as _) }} else {{ None }};",
+                    trampoline.name, bounds_str
                 )));
             } else {
+                chunks.push(Chunk::Custom(format!(
+                    "let {0} = Some({0}_func{1} as _);",
+                    trampoline.name, bounds_str
+                )));
+            }
+        } else {
+            chunks.push(Chunk::Custom(format!(
+                "let destroy_call{} = Some({}_func{} as _);",
+                trampoline.destroy_index, trampoline.name, bounds_str
+            )));
+        }
+    }
+
+    /// Boxes the callback unless it's called or shared, and adds the `{name}_func`
+    /// trampoline calling it.
+    fn add_trampoline_func(
+        &self,
+        env: &Env,
+        chunks: &mut Vec<Chunk>,
+        trampoline: &Trampoline,
+        full_type: &Option<(String, String)>,
+        pos: Option<usize>,
+        bounds: &str,
+        is_destroy: bool,
+    ) {
+        if !is_destroy {
+            if full_type.is_some() || trampoline.scope.is_call() {
                 chunks.push(Chunk::Custom(format!(
                     "let {0}_data: {1} = {0};",
                     trampoline.name, trampoline.bound_name
                 )));
+            } else {
+                chunks.push(Chunk::Custom(format!(
+                    "let {0}_data: Box_<{1}> = Box_::new({0});",
+                    trampoline.name, trampoline.bound_name
+                )));
             }
         }
 
@@ -443,39 +446,33 @@ impl Builder {
                     name: format!("{}callback", if is_destroy { "_" } else { "" }),
                     is_mut: false,
                     value: Box::new(Chunk::Custom(format!("Box_::from_raw({} as *mut _)", func))),
-                    type_: Some(Box::new(Chunk::Custom(full_type.1.clone()))),
+                    type_: Some(Box::new(Chunk::Custom(full_type.0.clone()))),
                 });
-            } else {
+            } else if trampoline.scope.is_call() {
                 body.push(Chunk::Let {
                     name: "callback".to_owned(),
                     is_mut: false,
                     value: Box::new(Chunk::Custom(format!(
-                        "{}*({} as *mut _)",
-                        if !trampoline.scope.is_call() {
-                            "&"
-                        } else if pos.is_some() {
-                            "&mut "
-                        } else {
-                            ""
-                        },
-                        func
-                    ))),
-                    type_: Some(Box::new(Chunk::Custom(
-                        if !trampoline.scope.is_async() && !trampoline.scope.is_call() {
-                            format!("&{}", full_type.1)
-                        } else {
-                            full_type.1.clone()
-                        },
+                        "{} as *const _ as usize as {}",
+                        func, full_type.1
                     ))),
+                    type_: Some(Box::new(Chunk::Custom(full_type.1.clone()))),
+                });
+            } else {
+                body.push(Chunk::Let {
+                    name: "callback".to_owned(),
+                    is_mut: false,
+                    value: Box::new(Chunk::Custom(format!("&*({} as *mut _)", func))),
+                    type_: Some(Box::new(Chunk::Custom(format!("&{}", full_type.1)))),
                 });
+            }
+            if !is_destroy {
+                // Only the callback of this trampoline is taken from the tuple
+                let pos = pos.expect("callback without position in the user data");
                 if trampoline.scope.is_async() {
                     body.push(Chunk::Custom(format!(
-                        "let callback = callback{}{};",
-                        if let Some(pos) = pos {
-                            format!(".{}", pos)
-                        } else {
-                            String::new()
-                        },
+                        "let callback = callback.{}{};",
+                        pos,
                         if *trampoline.nullable {
                             ".expect(\"cannot get closure...\")"
                         } else {
@@ -485,57 +482,33 @@ impl Builder {
                     if trampoline.ret.c_type != "void" {
                         extra_before_call = "let res = ";
                     }
-                } else if !trampoline.scope.is_call() {
-                    if *trampoline.nullable {
-                        body.push(Chunk::Custom(format!(
-                            "{}if let Some(ref callback) = callback{} {{",
-                            if trampoline.ret.c_type != "void" {
-                                "let res = "
-                            } else {
-                                ""
-                            },
-                            if let Some(pos) = pos {
-                                format!(".{}", pos)
-                            } else {
-                                String::new()
-                            }
-                        )));
-                    } else {
-                        body.push(Chunk::Custom(format!(
-                            "let callback = callback{}",
-                            if let Some(pos) = pos {
-                                format!(".{}", pos)
-                            } else {
-                                String::new()
-                            }
-                        )));
+                } else if *trampoline.nullable {
+                    body.push(Chunk::Custom(format!(
+                        "{}if let Some(ref {}callback) = (*callback).{} {{",
                         if trampoline.ret.c_type != "void" {
-                            body.push(Chunk::Custom("let res = ".to_owned()));
-                        }
-                    }
+                            "let res = "
+                        } else {
+                            ""
+                        },
+                        if trampoline.scope.is_call() {
+                            "mut "
+                        } else {
+                            ""
+                        },
+                        pos
+                    )));
                 } else {
-                    let add = if trampoline.ret.c_type != "void" {
-                        "let res = "
-                    } else {
-                        ""
-                    };
-                    if !trampoline.scope.is_async() && *trampoline.nullable {
-                        body.push(Chunk::Custom(format!(
-                            "{}if let Some(ref {}callback) = {} {{",
-                            add,
-                            if trampoline.scope.is_call() {
-                                "mut "
-                            } else {
-                                ""
-                            },
-                            if let Some(pos) = pos {
-                                format!("(*callback).{}", pos)
-                            } else {
-                                "*callback".to_owned()
-                            }
-                        )));
-                    } else {
-                        body.push(Chunk::Custom(add.to_owned()));
+                    body.push(Chunk::Custom(format!(
+                        "let callback = &{}(*callback).{};",
+                        if trampoline.scope.is_call() {
+                            "mut "
+                        } else {
+                            ""
+                        },
+                        pos
+                    )));
+                    if trampoline.ret.c_type != "void" {
+                        extra_before_call = "let res = ";
                     }
                 }
             }
@@ -673,29 +646,6 @@ impl Builder {
         };
 
         chunks.push(extern_func);
-        let bounds_str = if bounds_names.is_empty() {
-            String::new()
-        } else {
-            format!("::<{}>", bounds_names)
-        };
-        if !is_destroy {
-            if *trampoline.nullable {
-                chunks.push(Chunk::Custom(format!(
-                    "let {0} = if {0}_data.is_some() {{ Some({0}_func{1} as _) }} else {{ None }};",
-                    trampoline.name, bounds_str
-                )));
-            } else {
-                chunks.push(Chunk::Custom(format!(
-                    "let {0} = Some({0}_func{1} as _);",
-                    trampoline.name, bounds_str
-                )));
-            }
-        } else {
-            chunks.push(Chunk::Custom(format!(
-                "let destroy_call{} = Some({}_func{} as _);",
-                trampoline.destroy_index, trampoline.name, bounds_str
-            )));
-        }
     }
 
     fn add_async_trampoline(
@@ -703,16 +653,49 @@ impl Builder {
         env: &Env,
         chunks: &mut Vec<Chunk>,
         trampoline: &AsyncTrampoline,
+        bounds: &str,
+        bounds_names: &str,
     ) {
+        // The progress callback is stored next to the async callback in the user data
+        let user_data_type = match trampoline.progress {
+            Some(ref progress) => {
+                let tuple = format!("({}, {})", trampoline.bound_name, progress.bound_name);
+                let user_data_type = format!("Box_<{}>", tuple);
+                self.add_trampoline(
+                    env,
+                    chunks,
+                    progress,
+                    &Some((user_data_type.clone(), tuple)),
+                    Some(1),
+                    bounds,
+                    bounds_names,
+                    false,
+                );
+                user_data_type
+            }
+            None => format!("Box_<{}>", trampoline.bound_name),
+        };
         chunks.push(Chunk::Let {
             name: "user_data".to_string(),
             is_mut: false,
-            value: Box::new(Chunk::Custom("Box_::new(callback)".into())),
-            type_: Some(Box::new(Chunk::Custom(format!(
-                "Box_<{}>",
-                trampoline.bound_name
-            )))),
+            value: Box::new(Chunk::Custom(match trampoline.progress {
+                Some(ref progress) => format!("Box_::new((callback, {}_data))", progress.name),
+                None => "Box_::new(callback)".into(),
+            })),
+            type_: Some(Box::new(Chunk::Custom(user_data_type.clone()))),
         });
+        // Both callbacks get the same pointer, owned by the async callback
+        if let Some(ref progress) = trampoline.progress {
+            chunks.push(Chunk::Let {
+                name: "user_data".to_string(),
+                is_mut: false,
+                value: Box::new(Chunk::Custom("Box_::into_raw(user_data)".into())),
+                type_: Some(Box::new(Chunk::Custom(format!(
+                    "*mut ({}, {})",
+                    trampoline.bound_name, progress.bound_name
+                )))),
+            });
+        }
 
         let mut finish_args = vec![];
         let mut uninitialized_vars = Vec::new();
@@ -849,11 +832,16 @@ impl Builder {
             name: "callback".to_string(),
             is_mut: false,
             value: Box::new(Chunk::Custom("Box_::from_raw(user_data as *mut _)".into())),
-            type_: Some(Box::new(Chunk::Custom(format!(
-                "Box_<{}>",
-                trampoline.bound_name
-            )))),
+            type_: Some(Box::new(Chunk::Custom(user_data_type))),
         });
+        if trampoline.progress.is_some() {
+            body.push(Chunk::Let {
+                name: "callback".to_string(),
+                is_mut: false,
+                value: Box::new(Chunk::Custom("callback.0".into())),
+                type_: None,
+            });
+        }
         body.push(Chunk::Call {
             func_name: "callback".to_string(),
             arguments: vec![Chunk::Name("result".to_string())],
@@ -874,22 +862,36 @@ impl Builder {
             },
         ];
 
+        // With a progress callback, the user data depends on all the bounds
+        let (name, bounds, bound_names) = if trampoline.progress.is_some() {
+            (
+                trampoline.name.clone(),
+                bounds.to_owned(),
+                bounds_names.to_owned(),
+            )
+        } else {
+            (
+                format!(
+                    "{}<{}: {}>",
+                    trampoline.name, trampoline.bound_name, trampoline.callback_type
+                ),
+                String::new(),
+                trampoline.bound_name.to_string(),
+            )
+        };
         chunks.push(Chunk::ExternCFunc {
-            name: format!(
-                "{}<{}: {}>",
-                trampoline.name, trampoline.bound_name, trampoline.callback_type
-            ),
+            name,
             parameters,
             body: Box::new(Chunk::Chunks(body)),
             return_value: None,
-            bounds: String::new(),
+            bounds,
         });
         let chunk = Chunk::Let {
             name: "callback".to_string(),
             is_mut: false,
             value: Box::new(Chunk::Name(format!(
                 "{}::<{}>",
-                trampoline.name, trampoline.bound_name
+                trampoline.name, bound_names
             ))),
             type_: None,
         };
@@ -933,6 +935,60 @@ impl Builder {
         }
     }
 
+    /// Replaces the Rust closures passed for `GClosure` parameters by a `glib::Closure` calling
+    /// their trampoline through the generic marshaller, and freeing them with the closure.
+    fn add_closures(&self, env: &Env, chunks: &mut Vec<Chunk>, bounds: &str, bounds_names: &str) {
+        let bounds_str = if bounds_names.is_empty() {
+            String::new()
+        } else {
+            format!("::<{}>", bounds_names)
+        };
+        for closure in &self.closures {
+            self.add_trampoline_func(env, chunks, closure, &None, None, bounds, false);
+            chunks.push(Chunk::ExternCFunc {
+                name: format!("{}_notify", closure.name),
+                parameters: vec![
+                    Param {
+                        name: "data".to_owned(),
+                        typ: use_glib_if_needed(env, "ffi::gpointer"),
+                    },
+                    Param {
+                        name: "_closure".to_owned(),
+                        typ: format!("*mut {}", use_glib_type(env, "gobject_ffi::GClosure")),
+                    },
+                ],
+                body: Box::new(Chunk::Let {
+                    name: "_callback".to_owned(),
+                    is_mut: false,
+                    value: Box::new(Chunk::Custom("Box_::from_raw(data as *mut _)".to_owned())),
+                    type_: Some(Box::new(Chunk::Custom(format!(
+                        "Box_<{}>",
+                        closure.bound_name
+                    )))),
+                }),
+                return_value: None,
+                bounds: bounds.to_owned(),
+            });
+            chunks.push(Chunk::Let {
+                name: closure.name.clone(),
+                is_mut: false,
+                value: Box::new(Chunk::Custom(format!(
+                    "unsafe {{ from_glib_none({0}(Some(transmute::<_, unsafe extern \"C\" fn()>({1}_func{2} as *const ())), Box_::into_raw({1}_data) as *mut _, Some({1}_notify{2}))) }}",
+                    use_glib_type(env, "gobject_ffi::g_cclosure_new"),
+                    closure.name,
+                    bounds_str
+                ))),
+                type_: Some(Box::new(Chunk::Custom(use_glib_type(env, "Closure")))),
+            });
+            chunks.push(Chunk::Custom(format!(
+                "unsafe {{ {}({}.to_glib_none().0, Some({})) }};",
+                use_glib_type(env, "gobject_ffi::g_closure_set_marshal"),
+                closure.name,
+                use_glib_type(env, "gobject_ffi::g_cclosure_marshal_generic")
+            )));
+        }
+    }
+
     fn generate_call(&self, calls: &FuncParameters<'_>) -> Chunk {
         let params = self.generate_func_parameters(calls);
         let func = Chunk::FfiCall {
@@ -956,12 +1012,27 @@ impl Builder {
     }
     fn generate_func_parameters(&self, calls: &FuncParameters<'_>) -> Vec<Chunk> {
         let mut params = Vec::new();
+        // The user data of the progress callback is the one of the async callback
+        let progress_user_data_index = self
+            .async_trampoline
+            .as_ref()
+            .and_then(|trampoline| trampoline.progress.as_ref())
+            .map(|progress| progress.user_data_index);
         for trans in &self.transformations {
             if !trans.transformation_type.is_to_glib() {
                 continue;
             }
             let par = &self.parameters[trans.ind_c];
+            let is_user_data = Some(trans.ind_c) == progress_user_data_index
+                || matches!(trans.transformation_type, TransformationType::IntoRaw(_));
             let chunk = match *par {
+                In if progress_user_data_index.is_some() && is_user_data => {
+                    Chunk::FfiCallParameter {
+                        transformation_type: TransformationType::ToGlibDirect {
+                            name: "user_data as *mut _".to_owned(),
+                        },
+                    }
+                }
                 In => Chunk::FfiCallParameter {
                     transformation_type: trans.transformation_type.clone(),
                 },
@@ -978,7 +1049,9 @@ impl Builder {
                 *user_data_index,
                 Chunk::FfiCallParameter {
                     transformation_type: TransformationType::ToGlibDirect {
-                        name: if all_call {
+                        name: if all_call && callbacks.len() > 1 {
+                            format!("&mut super_callback{} as *mut _ as *mut _", pos)
+                        } else if all_call {
                             format!("super_callback{} as *const _ as usize as *mut _", pos)
                         } else {
                             format!("Box_::into_raw(super_callback{}) as *mut _", pos)
@@ -1134,8 +1207,12 @@ impl Builder {
                     type_: Option::None,
                 };
                 let ret = ret.expect("No return in optional outs mode");
+                // The out parameters aren't initialized when the function returns false
+                let mut init = Vec::new();
+                self.write_out_uninitialized(&mut init, mem::take(uninitialized_vars));
                 let ret = Chunk::OptionalReturn {
                     condition: "ret".into(),
+                    init,
                     value: Box::new(ret),
                 };
                 (call, Some(ret))
@@ -1235,6 +1312,7 @@ fn c_type_mem_mode_lib(
 ) -> OutMemMode {
     use self::OutMemMode::*;
     match ConversionType::of(env, typ) {
+        ConversionType::Pointer if is_direct_fixed_array(env, typ) => Uninitialized,
         ConversionType::Pointer => {
             if caller_allocates {
                 UninitializedNamed(rust_type(env, typ).unwrap())
@@ -1272,6 +1350,9 @@ fn c_type_mem_mode(env: &Env, parameter: &AnalysisCParameter) -> OutMemMode {
 
 fn type_mem_mode(env: &Env, parameter: &library::Parameter) -> Chunk {
     match ConversionType::of(env, parameter.typ) {
+        ConversionType::Pointer if is_direct_fixed_array(env, parameter.typ) => {
+            Chunk::Uninitialized
+        }
         ConversionType::Pointer => {
             if parameter.caller_allocates {
                 Chunk::UninitializedNamed {
@@ -1369,3 +1450,268 @@ fn add_chunk_for_type(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::env::tests::env;
+
+    const WIDGET: &str = r#"
+    <callback name="WidgetActivate" c:type="TestWidgetActivate">
+      <return-value transfer-ownership="none"><type name="gboolean" c:type="gboolean"/></return-value>
+      <parameters>
+        <parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></parameter>
+        <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1" closure="1"><type name="gpointer" c:type="gpointer"/></parameter>
+      </parameters>
+    </callback>
+    <callback name="WidgetChanged" c:type="TestWidgetChanged">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></parameter>
+        <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1" closure="1"><type name="gpointer" c:type="gpointer"/></parameter>
+      </parameters>
+    </callback>
+    <class name="Widget" c:symbol-prefix="widget" c:type="TestWidget" parent="GObject.Object" glib:type-name="TestWidget" glib:get-type="test_widget_get_type">
+      <method name="set_handlers" c:identifier="test_widget_set_handlers">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="activate" transfer-ownership="none" scope="notified" closure="2" destroy="3"><type name="WidgetActivate" c:type="TestWidgetActivate"/></parameter>
+          <parameter name="changed" transfer-ownership="none" nullable="1" allow-none="1" scope="notified" closure="2" destroy="3"><type name="WidgetChanged" c:type="TestWidgetChanged"/></parameter>
+          <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1"><type name="gpointer" c:type="gpointer"/></parameter>
+          <parameter name="destroy" transfer-ownership="none" scope="async"><type name="GLib.DestroyNotify" c:type="GDestroyNotify"/></parameter>
+        </parameters>
+      </method>
+      <method name="foreach_pair" c:identifier="test_widget_foreach_pair">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="first" transfer-ownership="none" scope="call" closure="2"><type name="WidgetChanged" c:type="TestWidgetChanged"/></parameter>
+          <parameter name="second" transfer-ownership="none" scope="call" closure="2"><type name="WidgetChanged" c:type="TestWidgetChanged"/></parameter>
+          <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1"><type name="gpointer" c:type="gpointer"/></parameter>
+        </parameters>
+      </method>
+      <method name="set_finalizer" c:identifier="test_widget_set_finalizer">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="func" transfer-ownership="none" scope="forever" closure="1"><type name="WidgetChanged" c:type="TestWidgetChanged"/></parameter>
+          <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1"><type name="gpointer" c:type="gpointer"/></parameter>
+        </parameters>
+      </method>
+      <method name="copy_async" c:identifier="test_widget_copy_async">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="cancellable" transfer-ownership="none" nullable="1" allow-none="1"><type name="Gio.Cancellable" c:type="GCancellable*"/></parameter>
+          <parameter name="progress_callback" transfer-ownership="none" scope="call" closure="2"><type name="Gio.FileProgressCallback" c:type="GFileProgressCallback"/></parameter>
+          <parameter name="progress_callback_data" transfer-ownership="none" nullable="1" allow-none="1"><type name="gpointer" c:type="gpointer"/></parameter>
+          <parameter name="callback" transfer-ownership="none" nullable="1" allow-none="1" scope="async" closure="4"><type name="Gio.AsyncReadyCallback" c:type="GAsyncReadyCallback"/></parameter>
+          <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1"><type name="gpointer" c:type="gpointer"/></parameter>
+        </parameters>
+      </method>
+      <method name="copy_finish" c:identifier="test_widget_copy_finish" throws="1">
+        <return-value transfer-ownership="none"><type name="gboolean" c:type="gboolean"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="res" transfer-ownership="none"><type name="Gio.AsyncResult" c:type="GAsyncResult*"/></parameter>
+        </parameters>
+      </method>
+      <method name="set_activate_closure" c:identifier="test_widget_set_activate_closure">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="closure" transfer-ownership="none"><type name="GObject.Closure" c:type="GClosure*"/></parameter>
+        </parameters>
+      </method>
+      <method name="get_size" c:identifier="test_widget_get_size">
+        <return-value transfer-ownership="none"><type name="gboolean" c:type="gboolean"/></return-value>
+        <parameters>
+          <instance-parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></instance-parameter>
+          <parameter name="width" direction="out" caller-allocates="0" transfer-ownership="full"><type name="gint" c:type="gint*"/></parameter>
+          <parameter name="height" direction="out" caller-allocates="0" transfer-ownership="full"><type name="gint" c:type="gint*"/></parameter>
+        </parameters>
+      </method>
+    </class>
+    <function name="add_changed_closure" c:identifier="test_add_changed_closure">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="closure" transfer-ownership="none"><type name="GObject.Closure" c:type="GClosure*"/></parameter>
+      </parameters>
+    </function>"#;
+
+    const CONFIG: &str = r#"
+[[object]]
+name = "Gio.Cancellable"
+status = "manual"
+
+[[object]]
+name = "Test.*"
+status = "generate"
+    [[object.function]]
+    name = "add_changed_closure"
+        [[object.function.parameter]]
+        name = "closure"
+        closure_signature = "Test.WidgetChanged"
+
+[[object]]
+name = "Test.Widget"
+status = "generate"
+    [[object.function]]
+    name = "set_activate_closure"
+        [[object.function.parameter]]
+        name = "closure"
+        closure_signature = "Test.WidgetActivate"
+"#;
+
+    /// Returns the generated function `name`.
+    fn function(code: &str, name: &str) -> String {
+        let start = code
+            .find(&format!("pub fn {}", name))
+            .unwrap_or_else(|| panic!("no function {} in:\n{}", name, code));
+        let end = code[start..].find("\n    }\n").unwrap();
+        code[start..start + end].to_owned()
+    }
+
+    #[test]
+    fn closure_trampoline() {
+        let code = env(WIDGET, CONFIG).generate("widget.rs");
+        assert!(!code.contains("ToValue"));
+        let code = function(&code, "set_activate_closure");
+        assert!(code.contains(
+            "pub fn set_activate_closure<P: Fn(&Widget) -> bool + 'static>(&self, closure: P) {"
+        ));
+        assert!(code.contains("let closure_data: Box_<P> = Box_::new(closure);"));
+        // Called through a trampoline like the callbacks
+        assert!(code.contains(
+            "unsafe extern \"C\" fn closure_func<P: Fn(&Widget) -> bool + 'static>(widget: *mut ffi::TestWidget, user_data: glib::ffi::gpointer) -> glib::ffi::gboolean {"
+        ));
+        assert!(code.contains("let callback: &P = &*(user_data as *mut _);"));
+        // Freed with the `GClosure`
+        assert!(code.contains(
+            "unsafe extern \"C\" fn closure_notify<P: Fn(&Widget) -> bool + 'static>(data: glib::ffi::gpointer, _closure: *mut glib::gobject_ffi::GClosure) {\n            let _callback: Box_<P> = Box_::from_raw(data as *mut _);"
+        ));
+        assert!(code.contains(
+            "let closure: glib::Closure = unsafe { from_glib_none(glib::gobject_ffi::g_cclosure_new(Some(transmute::<_, unsafe extern \"C\" fn()>(closure_func::<P> as *const ())), Box_::into_raw(closure_data) as *mut _, Some(closure_notify::<P>))) };"
+        ));
+        assert!(code.contains(
+            "glib::gobject_ffi::g_closure_set_marshal(closure.to_glib_none().0, Some(glib::gobject_ffi::g_cclosure_marshal_generic))"
+        ));
+        assert!(code.contains(
+            "ffi::test_widget_set_activate_closure(self.to_glib_none().0, closure.to_glib_none().0);"
+        ));
+    }
+
+    #[test]
+    fn send_sync_closure() {
+        let code = env(WIDGET, CONFIG).generate("functions.rs");
+        assert!(code.contains("use std::mem::transmute;"));
+        assert!(code.contains("ffi::test_add_changed_closure(closure.to_glib_none().0);"));
+        let code = function(&code, "add_changed_closure");
+        assert!(code.contains(
+            "pub fn add_changed_closure<P: Fn(&Widget) + Send + Sync + 'static>(closure: P) {"
+        ));
+        assert!(code.contains("(*callback)(&widget);"));
+    }
+
+    #[test]
+    fn notified_callbacks_sharing_user_data() {
+        let code = env(WIDGET, CONFIG).generate("widget.rs");
+        let code = function(&code, "set_handlers");
+        let tuple = "(P, Option<Box_<dyn Fn(&Widget) + 'static>>)";
+        assert!(code.contains(&format!(
+            "let callback: &{} = &*(user_data as *mut _);",
+            tuple
+        )));
+        assert!(code.contains("let callback = &(*callback).0;"));
+        assert!(code.contains("if let Some(ref callback) = (*callback).1 {"));
+        // Freed once by the destroy notify
+        assert!(code.contains(&format!(
+            "let _callback: Box_<{}> = Box_::from_raw(data as *mut _);",
+            tuple
+        )));
+        assert!(code.contains(&format!(
+            "let super_callback0: Box_<{}> = Box_::new((activate_data, changed_data));",
+            tuple
+        )));
+        assert!(code.contains("Box_::into_raw(super_callback0) as *mut _, destroy_call4)"));
+    }
+
+    #[test]
+    fn call_callbacks_sharing_user_data() {
+        let code = env(WIDGET, CONFIG).generate("widget.rs");
+        let code = function(&code, "foreach_pair");
+        assert!(code.contains("pub fn foreach_pair<P: FnMut(&Widget), Q: FnMut(&Widget)>"));
+        assert!(code.contains(
+            "let callback: *mut (P, Q) = user_data as *const _ as usize as *mut (P, Q);"
+        ));
+        assert!(code.contains("let callback = &mut (*callback).0;"));
+        assert!(code.contains("let callback = &mut (*callback).1;"));
+        // Mutated by the trampolines, so borrowed mutably
+        assert!(code.contains("let mut super_callback0: (P, Q) = (first_data, second_data);"));
+        assert!(code.contains("&mut super_callback0 as *mut _ as *mut _)"));
+    }
+
+    #[test]
+    fn forever_callback() {
+        let code = env(WIDGET, CONFIG).generate("widget.rs");
+        // Documented on the function
+        assert!(code.contains(
+            "    /// `func` is called until the end of the program, it's leaked and never freed.\n    #[doc(alias = \"test_widget_set_finalizer\")]\n    pub fn set_finalizer"
+        ));
+        let code = function(&code, "set_finalizer");
+        assert!(code.contains("pub fn set_finalizer<P: Fn(&Widget) + 'static>(&self, func: P)"));
+        assert!(code.contains("let func_data: Box_<P> = Box_::new(func);"));
+        assert!(code.contains("let callback: &P = &*(user_data as *mut _);"));
+        assert!(!code.contains("// `func` is called"));
+        assert!(code.contains("Box_::into_raw(super_callback0) as *mut _)"));
+        // Never freed
+        assert!(!code.contains("Box_::from_raw"));
+    }
+
+    #[test]
+    fn async_progress_callback() {
+        let code = env(WIDGET, CONFIG).generate("widget.rs");
+        let code = function(&code, "copy_async");
+        // Stored with the async callback, and freed with it
+        assert!(code.contains(
+            "let user_data: Box_<(Q, P)> = Box_::new((callback, progress_callback_data));"
+        ));
+        assert!(code.contains("let user_data: *mut (Q, P) = Box_::into_raw(user_data);"));
+        assert!(code.contains("let callback: &(Q, P) = &*(user_data as *mut _);"));
+        assert!(code.contains("let callback = &(*callback).1;"));
+        assert!(code.contains("let callback: Box_<(Q, P)> = Box_::from_raw(user_data as *mut _);"));
+        // The same pointer is passed to both callbacks
+        assert!(code.contains(
+            "progress_callback, user_data as *mut _, Some(callback), user_data as *mut _)"
+        ));
+    }
+
+    #[test]
+    fn optional_outs_read_on_success() {
+        let code = env(WIDGET, CONFIG).generate("widget.rs");
+        let code = function(&code, "get_size");
+        assert!(code.contains("pub fn get_size(&self) -> Option<(i32, i32)> {"));
+        // The out parameters are only initialized when the function returned true
+        assert!(code.contains(
+            "if ret {\n                let width = width.assume_init();\n                let height = height.assume_init();\n                Some((width, height))\n            } else {\n                None\n            }"
+        ));
+
+        let config = format!(
+            "{}    [[object.function]]\n    name = \"get_size\"\n        [object.function.return]\n        bool_return_is_none = false\n",
+            CONFIG
+        );
+        let code = env(WIDGET, &config).generate("widget.rs");
+        let code = function(&code, "get_size");
+        assert!(code.contains("pub fn get_size(&self) -> (bool, i32, i32) {"));
+        assert!(!code.contains("if ret {"));
+
+        // Takes precedence over `nullable = false`
+        let config = format!(
+            "{}    [[object.function]]\n    name = \"get_size\"\n        [object.function.return]\n        nullable = false\n        bool_return_is_none = true\n",
+            CONFIG
+        );
+        let code = env(WIDGET, &config).generate("widget.rs");
+        let code = function(&code, "get_size");
+        assert!(code.contains("pub fn get_size(&self) -> Option<(i32, i32)> {"));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    analysis::info_base::InfoBase,
     codegen::{function, general},
     env::Env,
     file_saver,
@@ -9,19 +10,30 @@ use std::path::Path;
 pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
     info!("Generate global functions");
 
-    let functions = match env.analysis.global_functions {
-        Some(ref functions) => functions,
-        None => return,
-    };
+    if let Some(ref functions) = env.analysis.global_functions {
+        generate_module(env, root_path, mod_rs, functions, "functions");
+    }
 
-    let path = root_path.join("functions.rs");
+    for functions in &env.analysis.global_functions_modules {
+        generate_module(env, root_path, mod_rs, functions, &functions.name);
+    }
+}
+
+fn generate_module(
+    env: &Env,
+    root_path: &Path,
+    mod_rs: &mut Vec<String>,
+    functions: &InfoBase,
+    module_name: &str,
+) {
+    let path = root_path.join(format!("{}.rs", module_name));
     file_saver::save_to_file(path, env.config.make_backup, |w| {
         general::start_comments(w, &env.config)?;
         general::uses(w, env, &functions.imports)?;
 
         writeln!(w)?;
 
-        mod_rs.push("\npub mod functions;".into());
+        mod_rs.push(format!("\npub mod {};", module_name));
 
         for func_analysis in &functions.functions {
             function::generate(w, env, func_analysis, None, false, false, 0)?;
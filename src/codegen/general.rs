@@ -367,6 +367,137 @@ pub fn define_auto_boxed_type(
     Ok(())
 }
 
+/// Defines a record or union copied by value as a transparent wrapper around the FFI type.
+pub fn define_direct_type(
+    w: &mut dyn Write,
+    env: &Env,
+    type_name: &str,
+    glib_name: &str,
+    get_type_fn: Option<&str>,
+    derive: &[Derive],
+) -> Result<()> {
+    let name = type_name;
+    let ffi_name = format!("{}::{}", env.main_sys_crate_name(), glib_name);
+
+    writeln!(w)?;
+    derives_with_defaults(w, derive, &["Clone", "Copy"], 0)?;
+    writeln!(w, "#[repr(transparent)]")?;
+    doc_alias(w, glib_name, "", 0)?;
+    writeln!(w, "pub struct {}({});", name, ffi_name)?;
+
+    writeln!(w)?;
+    writeln!(w, "impl {} {{", name)?;
+    writeln!(w, "\t#[doc(hidden)]")?;
+    writeln!(w, "\tpub unsafe fn uninitialized() -> Self {{")?;
+    writeln!(w, "\t\tSelf(std::mem::zeroed())")?;
+    writeln!(w, "\t}}")?;
+    writeln!(w, "}}")?;
+
+    writeln!(
+        w,
+        "
+#[doc(hidden)]
+impl FromGlib<{ffi_name}> for {name} {{
+    unsafe fn from_glib(value: {ffi_name}) -> Self {{
+        Self(value)
+    }}
+}}
+
+#[doc(hidden)]
+impl<'a> ToGlibPtr<'a, *const {ffi_name}> for {name} {{
+    type Storage = &'a Self;
+
+    fn to_glib_none(&'a self) -> Stash<'a, *const {ffi_name}, Self> {{
+        Stash(&self.0, self)
+    }}
+
+    fn to_glib_full(&self) -> *const {ffi_name} {{
+        unsafe {{
+            let ptr = {g_malloc}(std::mem::size_of::<{ffi_name}>()) as *mut {ffi_name};
+            *ptr = self.0;
+            ptr
+        }}
+    }}
+}}
+
+#[doc(hidden)]
+impl<'a> ToGlibPtr<'a, *mut {ffi_name}> for {name} {{
+    type Storage = &'a Self;
+
+    fn to_glib_none(&'a self) -> Stash<'a, *mut {ffi_name}, Self> {{
+        Stash(&self.0 as *const _ as *mut _, self)
+    }}
+}}
+
+#[doc(hidden)]
+impl<'a> ToGlibPtrMut<'a, *mut {ffi_name}> for {name} {{
+    type Storage = &'a mut Self;
+
+    fn to_glib_none_mut(&'a mut self) -> StashMut<'a, *mut {ffi_name}, Self> {{
+        let ptr = &mut self.0 as *mut _;
+        StashMut(ptr, self)
+    }}
+}}
+
+#[doc(hidden)]
+impl FromGlibPtrNone<*const {ffi_name}> for {name} {{
+    unsafe fn from_glib_none(ptr: *const {ffi_name}) -> Self {{
+        assert!(!ptr.is_null());
+        Self(*ptr)
+    }}
+}}
+
+#[doc(hidden)]
+impl FromGlibPtrNone<*mut {ffi_name}> for {name} {{
+    unsafe fn from_glib_none(ptr: *mut {ffi_name}) -> Self {{
+        assert!(!ptr.is_null());
+        Self(*ptr)
+    }}
+}}
+
+#[doc(hidden)]
+impl FromGlibPtrFull<*mut {ffi_name}> for {name} {{
+    unsafe fn from_glib_full(ptr: *mut {ffi_name}) -> Self {{
+        assert!(!ptr.is_null());
+        let res = Self(*ptr);
+        {g_free}(ptr as *mut _);
+        res
+    }}
+}}
+
+#[doc(hidden)]
+impl FromGlibPtrBorrow<*mut {ffi_name}> for {name} {{
+    unsafe fn from_glib_borrow(ptr: *mut {ffi_name}) -> Borrowed<Self> {{
+        assert!(!ptr.is_null());
+        Borrowed::new(Self(*ptr))
+    }}
+}}",
+        name = name,
+        ffi_name = ffi_name,
+        g_malloc = use_glib_type(env, "ffi::g_malloc"),
+        g_free = use_glib_type(env, "ffi::g_free"),
+    )?;
+
+    if let Some(get_type_fn) = get_type_fn {
+        writeln!(
+            w,
+            "
+impl {static_type} for {name} {{
+    fn static_type() -> {type_} {{
+        unsafe {{ from_glib({sys_crate_name}::{get_type}()) }}
+    }}
+}}",
+            static_type = use_glib_type(env, "StaticType"),
+            type_ = use_glib_type(env, "Type"),
+            name = name,
+            sys_crate_name = env.main_sys_crate_name(),
+            get_type = get_type_fn,
+        )?;
+    }
+
+    Ok(())
+}
+
 fn define_shared_type_internal(
     w: &mut dyn Write,
     env: &Env,
@@ -490,6 +621,56 @@ pub fn cfg_deprecated_string(
     }
 }
 
+/// Returns the cfg expression under which an item deprecated in `deprecated`
+/// is compiled when `options.deprecated_feature` is enabled.
+fn deprecated_feature_cfg(env: &Env, deprecated: Option<Version>) -> Option<String> {
+    if !env.config.deprecated_feature {
+        return None;
+    }
+    if env.is_too_low_version(deprecated) {
+        Some("feature = \"deprecated\"".to_owned())
+    } else {
+        deprecated.map(|v| format!("any(not({}), feature = \"deprecated\")", v.to_cfg()))
+    }
+}
+
+pub fn deprecated_feature_condition(
+    w: &mut dyn Write,
+    env: &Env,
+    deprecated: Option<Version>,
+    commented: bool,
+    indent: usize,
+) -> Result<()> {
+    if let Some(s) = deprecated_feature_condition_string(env, deprecated, commented, indent) {
+        writeln!(w, "{}", s)?;
+    }
+    Ok(())
+}
+
+pub fn deprecated_feature_condition_no_doc(
+    w: &mut dyn Write,
+    env: &Env,
+    deprecated: Option<Version>,
+    commented: bool,
+    indent: usize,
+) -> Result<()> {
+    if let Some(s) =
+        cfg_condition_string_no_doc(&deprecated_feature_cfg(env, deprecated), commented, indent)
+    {
+        writeln!(w, "{}", s)?;
+    }
+    Ok(())
+}
+
+pub fn deprecated_feature_condition_string(
+    env: &Env,
+    deprecated: Option<Version>,
+    commented: bool,
+    indent: usize,
+) -> Option<String> {
+    cfg_condition_string(&deprecated_feature_cfg(env, deprecated), commented, indent)
+}
+
 pub fn version_condition(
     w: &mut dyn Write,
     env: &Env,
@@ -635,6 +816,69 @@ pub fn derives(w: &mut dyn Write, derives: &[Derive], indent: usize) -> Result<(
     Ok(())
 }
 
+/// Writes `derives`, followed by the `defaults` they don't already derive, even under a
+/// condition to not derive the same trait twice. The defaults needing a trait derived
+/// under a condition are derived under the same condition.
+pub fn derives_with_defaults(
+    w: &mut dyn Write,
+    derives: &[Derive],
+    defaults: &[&str],
+    indent: usize,
+) -> Result<()> {
+    self::derives(w, derives, indent)?;
+    let mut groups: Vec<(Option<String>, Vec<&str>)> = Vec::new();
+    for &name in defaults {
+        if derives
+            .iter()
+            .any(|derive| derive.names.iter().any(|n| n == name))
+        {
+            continue;
+        }
+        let conditions = derives
+            .iter()
+            .filter(|derive| {
+                derive
+                    .names
+                    .iter()
+                    .any(|n| required_derives(name).contains(&n.as_str()))
+            })
+            .filter_map(|derive| derive.cfg_condition.as_deref())
+            .collect::<Vec<_>>();
+        let condition = match conditions.len() {
+            0 => None,
+            1 => Some(conditions[0].to_owned()),
+            _ => Some(format!("all({})", conditions.join(", "))),
+        };
+        match groups.iter_mut().find(|(c, _)| *c == condition) {
+            Some((_, names)) => names.push(name),
+            None => groups.push((condition, vec![name])),
+        }
+    }
+    for (condition, names) in groups {
+        match condition {
+            Some(condition) => writeln!(
+                w,
+                "{}#[cfg_attr({}, derive({}))]",
+                tabs(indent),
+                condition,
+                names.join(", ")
+            )?,
+            None => writeln!(w, "{}#[derive({})]", tabs(indent), names.join(", "))?,
+        }
+    }
+    Ok(())
+}
+
+/// The traits a derived trait needs to be implemented.
+fn required_derives(name: &str) -> &'static [&'static str] {
+    match name {
+        "Copy" => &["Clone"],
+        "Eq" | "PartialOrd" => &["PartialEq"],
+        "Ord" => &["PartialOrd", "Eq"],
+        _ => &[],
+    }
+}
+
 pub fn doc_alias(w: &mut dyn Write, name: &str, comment_prefix: &str, indent: usize) -> Result<()> {
     writeln!(
         w,
@@ -678,6 +922,7 @@ pub fn declare_default_from_new(
             && f.parameters.rust_parameters.is_empty()
     }) {
         writeln!(w)?;
+        deprecated_feature_condition(w, env, func.deprecated_version, false, 0)?;
         version_condition(w, env, func.version, false, 0)?;
         writeln!(w, "impl Default for {} {{", name)?;
         writeln!(w, "    fn default() -> Self {{")?;
@@ -712,4 +957,35 @@ mod tests {
         assert_eq!(escape_string("no escaping here"), "no escaping here");
         assert_eq!(escape_string(r#"'"\"#), r#"'\"\\"#);
     }
+
+    #[test]
+    fn derives_skip_configured_defaults() {
+        let derive = |names: &[&str], cfg_condition: Option<&str>| Derive {
+            names: names.iter().map(|&n| n.to_owned()).collect(),
+            cfg_condition: cfg_condition.map(ToOwned::to_owned),
+        };
+        let write = |derives: &[Derive]| {
+            let mut w = Vec::new();
+            derives_with_defaults(&mut w, derives, &["Clone", "Copy"], 0).unwrap();
+            String::from_utf8(w).unwrap()
+        };
+
+        assert_eq!(write(&[]), "#[derive(Clone, Copy)]\n");
+        assert_eq!(
+            write(&[derive(&["Debug", "Clone"], None)]),
+            "#[derive(Debug, Clone)]\n#[derive(Copy)]\n"
+        );
+        assert_eq!(
+            write(&[derive(&["Clone", "Copy"], None)]),
+            "#[derive(Clone, Copy)]\n"
+        );
+        assert_eq!(
+            write(&[derive(&["Clone"], Some("unix"))]),
+            "#[cfg_attr(unix, derive(Clone))]\n#[cfg_attr(unix, derive(Copy))]\n"
+        );
+        assert_eq!(
+            write(&[derive(&["Debug"], Some("unix"))]),
+            "#[cfg_attr(unix, derive(Debug))]\n#[derive(Clone, Copy)]\n"
+        );
+    }
 }
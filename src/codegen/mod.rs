@@ -1,7 +1,9 @@
 use crate::{config::WorkMode, env::Env, file_saver::*};
+use log::error;
 use std::path::Path;
 
 mod alias;
+mod cargo_toml;
 mod child_properties;
 mod constants;
 mod doc;
@@ -29,6 +31,9 @@ mod trampoline_from_glib;
 mod trampoline_to_glib;
 pub mod translate_from_glib;
 pub mod translate_to_glib;
+mod typed_error;
+mod union;
+mod unions;
 
 pub fn generate(env: &Env) {
     match env.config.work_mode {
@@ -47,13 +52,18 @@ fn normal_generate(env: &Env) {
     generate_single_version_file(env);
     objects::generate(env, root_path, &mut mod_rs, &mut traits);
     records::generate(env, root_path, &mut mod_rs);
+    unions::generate(env, root_path, &mut mod_rs);
     enums::generate(env, root_path, &mut mod_rs);
     flags::generate(env, root_path, &mut mod_rs);
     alias::generate(env, root_path, &mut mod_rs);
     functions::generate(env, root_path, &mut mod_rs);
     constants::generate(env, root_path, &mut mod_rs);
+    typed_error::generate(env, root_path, &mut mod_rs);
 
     generate_mod_rs(env, root_path, &mod_rs, &traits);
+    if let Err(e) = cargo_toml::generate(env) {
+        error!("Couldn't update Cargo.toml: {}", e);
+    }
 }
 
 pub fn generate_mod_rs(env: &Env, root_path: &Path, mod_rs: &[String], traits: &[String]) {
@@ -76,3 +86,147 @@ pub fn generate_single_version_file(env: &Env) {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::env::tests::env;
+    use std::fs;
+
+    const DEPRECATED: &str = r#"
+    <class name="Widget" c:symbol-prefix="widget" c:type="TestWidget" parent="GObject.Object" glib:type-name="TestWidget" glib:get-type="test_widget_get_type" deprecated="1" deprecated-version="1.2">
+    </class>
+    <record name="Point" c:type="TestPoint" glib:type-name="TestPoint" glib:get-type="test_point_get_type" c:symbol-prefix="point" deprecated="1" deprecated-version="1.4">
+      <field name="x" writable="1"><type name="gint" c:type="gint"/></field>
+    </record>
+    <class name="Canvas" c:symbol-prefix="canvas" c:type="TestCanvas" parent="GObject.Object" glib:type-name="TestCanvas" glib:get-type="test_canvas_get_type">
+      <method name="add" c:identifier="test_canvas_add">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="canvas" transfer-ownership="none"><type name="Canvas" c:type="TestCanvas*"/></instance-parameter>
+          <parameter name="widget" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></parameter>
+        </parameters>
+      </method>
+      <method name="get_origin" c:identifier="test_canvas_get_origin">
+        <return-value transfer-ownership="full"><type name="Point" c:type="TestPoint*"/></return-value>
+        <parameters>
+          <instance-parameter name="canvas" transfer-ownership="none"><type name="Canvas" c:type="TestCanvas*"/></instance-parameter>
+        </parameters>
+      </method>
+      <method name="get_size" c:identifier="test_canvas_get_size">
+        <return-value transfer-ownership="full"><type name="Size" c:type="TestSize*"/></return-value>
+        <parameters>
+          <instance-parameter name="canvas" transfer-ownership="none"><type name="Canvas" c:type="TestCanvas*"/></instance-parameter>
+        </parameters>
+      </method>
+      <property name="focus" writable="1" transfer-ownership="none"><type name="Widget" c:type="TestWidget*"/></property>
+      <glib:signal name="moved" when="last">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <parameter name="point" transfer-ownership="none"><type name="Point" c:type="TestPoint*"/></parameter>
+        </parameters>
+      </glib:signal>
+    </class>
+    <record name="Size" c:type="TestSize" glib:type-name="TestSize" glib:get-type="test_size_get_type" c:symbol-prefix="size">
+      <field name="width" writable="1"><type name="gint" c:type="gint"/></field>
+    </record>
+"#;
+
+    const CONFIG: &str = r#"deprecated_feature = true
+
+[[object]]
+name = "Test.Widget"
+status = "generate"
+
+[[object]]
+name = "Test.Point"
+status = "generate"
+
+[[object]]
+name = "Test.Size"
+status = "generate"
+
+[[object]]
+name = "Test.Canvas"
+status = "generate"
+"#;
+
+    /// Returns the attributes gating an item deprecated in `version` on the feature.
+    fn deprecated_cfg(version: &str) -> String {
+        let cfg = format!(
+            "any(not(feature = \"{}\"), feature = \"deprecated\")",
+            version
+        );
+        format!(
+            "#[cfg(any({}, feature = \"dox\"))]\n#[cfg_attr(feature = \"dox\", doc(cfg({})))]\n",
+            cfg, cfg
+        )
+    }
+
+    #[test]
+    fn deprecated_types_are_behind_feature() {
+        let code = env(DEPRECATED, CONFIG).generate("mod.rs");
+        let widget = deprecated_cfg("v1_2");
+        assert!(code.contains(&format!("{}mod widget;", widget)));
+        assert!(code.contains(&format!("{}pub use self::widget::{{Widget}};", widget)));
+        let point = deprecated_cfg("v1_4");
+        assert!(code.contains(&format!("{}mod point;", point)));
+        assert!(code.contains(&format!("{}pub use self::point::Point;", point)));
+        assert!(code.contains("\n\nmod size;\npub use self::size::Size;"));
+    }
+
+    #[test]
+    fn users_of_deprecated_types_are_behind_feature() {
+        let code = env(DEPRECATED, CONFIG).generate("canvas.rs");
+        let widget = deprecated_cfg("v1_2").replace("#[", "    #[");
+        let point = deprecated_cfg("v1_4").replace("#[", "    #[");
+        assert!(code.contains(&format!(
+            "{}    #[doc(alias = \"test_canvas_add\")]",
+            widget
+        )));
+        assert!(code.contains(&format!(
+            "{}    #[doc(alias = \"test_canvas_get_origin\")]",
+            point
+        )));
+        assert!(code.contains("\n\n    #[doc(alias = \"test_canvas_get_size\")]"));
+        assert!(code.contains(&format!("{}    pub fn get_property_focus(", widget)));
+        assert!(code.contains(&format!("{}    pub fn set_property_focus(", widget)));
+        assert!(code.contains(&format!("{}    pub fn connect_moved<", point)));
+        assert!(code.contains("\n\n    pub fn connect_property_focus_notify<"));
+    }
+
+    #[test]
+    fn deprecated_feature_declared_in_cargo_toml() {
+        let t = env(DEPRECATED, CONFIG);
+        let path = t.env.config.target_path.join("Cargo.toml");
+        fs::create_dir_all(&t.env.config.target_path).unwrap();
+        let manifest = "[package]\nname = \"test\" # the crate\nversion = \"0.0.1\"\n\n[features]\n# Versions\nv1_2 = []\n\n[dependencies]\nlibc = \"0.2\"\n";
+        fs::write(&path, manifest).unwrap();
+        t.generate("mod.rs");
+        // Inserted after the last feature, the rest of the file is kept as is
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            manifest.replace("v1_2 = []\n", "v1_2 = []\ndeprecated = []\n")
+        );
+
+        // Declared once
+        t.generate("mod.rs");
+        let manifest: toml::Value = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            manifest["features"]["deprecated"],
+            toml::Value::Array(Vec::new())
+        );
+    }
+
+    #[test]
+    fn features_table_added_to_cargo_toml() {
+        let t = env(DEPRECATED, CONFIG);
+        let path = t.env.config.target_path.join("Cargo.toml");
+        fs::create_dir_all(&t.env.config.target_path).unwrap();
+        fs::write(&path, "[package]\nname = \"test\"\n").unwrap();
+        t.generate("mod.rs");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[package]\nname = \"test\"\n\n[features]\ndeprecated = []\n"
+        );
+    }
+}
@@ -94,7 +94,9 @@ pub fn generate(
 
         writeln!(w, "}}")?;
 
-        general::declare_default_from_new(w, env, &analysis.name, &analysis.functions)?;
+        if !analysis.specials.has_trait(Type::Default) {
+            general::declare_default_from_new(w, env, &analysis.name, &analysis.functions)?;
+        }
     }
 
     trait_impls::generate(
@@ -205,7 +207,8 @@ fn generate_builder(w: &mut dyn Write, env: &Env, analysis: &analysis::object::I
                         let alias = property
                             .bounds
                             .get_parameter_alias_info(&property.name)
-                            .map(|(alias, _)| format!("&{}", alias));
+                            .and_then(|(alias, _)| alias)
+                            .map(|alias| format!("&{}", alias));
                         (alias, bounds, ".clone().upcast()")
                     }
                     typ if typ.starts_with('&') => (None, String::new(), ".clone()"),
@@ -215,15 +218,18 @@ fn generate_builder(w: &mut dyn Write, env: &Env, analysis: &analysis::object::I
                     param_type = param_type_override.to_string();
                 }
                 let name = nameutil::mangle_keywords(nameutil::signal_to_snake(&property.name));
-                let version_condition_string =
-                    version_condition_string(env, property.version, false, 1);
-                if let Some(ref version_condition_string) = version_condition_string {
-                    writeln!(w, "{}", version_condition_string)?;
-                }
+                let prefix: String = general::deprecated_feature_condition_string(
+                    env,
+                    property.deprecated_feature_version,
+                    false,
+                    1,
+                )
+                .into_iter()
+                .chain(version_condition_string(env, property.version, false, 1))
+                .map(|condition| format!("{}\n", condition))
+                .collect();
+                write!(w, "{}", prefix)?;
                 writeln!(w, "    {}: Option<{}>,", name, type_string)?;
-                let prefix = version_condition_string
-                    .map(|version| format!("{}\n", version))
-                    .unwrap_or_default();
                 methods.push(format!(
                     "\n{prefix}    pub fn {name}{bounds}(mut self, {name}: {param_type}) -> Self {{
         self.{name} = Some({name}{conversion});
@@ -261,6 +267,13 @@ impl {}Builder {{
     )?;
     for property in &properties {
         let name = nameutil::mangle_keywords(nameutil::signal_to_snake(&property.name));
+        general::deprecated_feature_condition_no_doc(
+            w,
+            env,
+            property.deprecated_feature_version,
+            false,
+            2,
+        )?;
         version_condition_no_doc(w, env, property.version, false, 2)?;
         writeln!(
             w,
@@ -380,6 +393,12 @@ pub fn generate_reexports(
     if let Some(cfg) = general::version_condition_string(env, analysis.version, false, 0) {
         cfgs.push(cfg);
     }
+    let deprecated_version = env.library.type_(analysis.type_id).get_deprecated_version();
+    if let Some(cfg) =
+        general::deprecated_feature_condition_string(env, deprecated_version, false, 0)
+    {
+        cfgs.push(cfg);
+    }
     contents.push("".to_owned());
     contents.extend_from_slice(&cfgs);
     contents.push(format!("mod {};", module_name));
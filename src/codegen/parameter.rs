@@ -6,16 +6,17 @@ use crate::{
         ref_mode::RefMode,
         rust_type::parameter_rust_type,
     },
+    codegen::function::bound_traits,
     env::Env,
     traits::*,
 };
 
 pub trait ToParameter {
-    fn to_parameter(&self, env: &Env, bounds: &Bounds) -> String;
+    fn to_parameter(&self, env: &Env, bounds: &Bounds, r#async: bool) -> String;
 }
 
 impl ToParameter for CParameter {
-    fn to_parameter(&self, env: &Env, bounds: &Bounds) -> String {
+    fn to_parameter(&self, env: &Env, bounds: &Bounds, r#async: bool) -> String {
         let ref_str = match self.ref_mode {
             RefMode::ByRefMut => "&mut ",
             RefMode::None => "",
@@ -25,15 +26,29 @@ impl ToParameter for CParameter {
             format!("{}self", ref_str)
         } else {
             let type_str: String;
-            match bounds.get_parameter_alias_info(&self.name) {
-                Some((t, bound_type)) => match bound_type {
-                    BoundType::NoWrapper => type_str = t.to_string(),
-                    BoundType::IsA(_) if *self.nullable => {
-                        type_str = format!("Option<{}{}>", ref_str, t)
+            match bounds.get_parameter_bound(&self.name) {
+                Some(bound) => {
+                    let t = match bound.alias {
+                        Some(alias) => alias.to_string(),
+                        None => {
+                            let traits = bound_traits(bound, r#async);
+                            // `&impl A + B` is ambiguous
+                            if bound.bound_type.need_isa() && traits.contains(" + ") {
+                                format!("(impl {})", traits)
+                            } else {
+                                format!("impl {}", traits)
+                            }
+                        }
+                    };
+                    match bound.bound_type {
+                        BoundType::NoWrapper => type_str = t,
+                        BoundType::IsA(_) if *self.nullable => {
+                            type_str = format!("Option<{}{}>", ref_str, t)
+                        }
+                        BoundType::IsA(_) => type_str = format!("{}{}", ref_str, t),
+                        BoundType::AsRef(_) => type_str = t,
                     }
-                    BoundType::IsA(_) => type_str = format!("{}{}", ref_str, t),
-                    BoundType::AsRef(_) => type_str = t.to_string(),
-                },
+                }
                 None => {
                     let rust_type = parameter_rust_type(
                         env,
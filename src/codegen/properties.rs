@@ -1,5 +1,5 @@
 use super::{
-    general::{cfg_deprecated, version_condition},
+    general::{cfg_deprecated, deprecated_feature_condition, version_condition},
     property_body,
 };
 use crate::{
@@ -49,6 +49,7 @@ fn generate_prop_func(
     if !in_trait || only_declaration {
         cfg_deprecated(w, env, prop.deprecated_version, commented, indent)?;
     }
+    deprecated_feature_condition(w, env, prop.deprecated_feature_version, commented, indent)?;
     version_condition(w, env, prop.version, commented, indent)?;
     writeln!(
         w,
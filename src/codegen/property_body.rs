@@ -128,6 +128,9 @@ impl<'a> Builder<'a> {
             commented: false,
             bool_return_is_error: None,
             nullable_return_is_error: None,
+            int_return_is_error: None,
+            int_return_is_none: None,
+            bool_return_is_none: None,
         };
         let ffi_call = Chunk::FfiCall {
             name: self.get_ffi_func(),
@@ -222,6 +225,9 @@ impl<'a> Builder<'a> {
             commented: false,
             bool_return_is_error: None,
             nullable_return_is_error: None,
+            int_return_is_error: None,
+            int_return_is_none: None,
+            bool_return_is_none: None,
         };
         body.push(Chunk::FfiCallConversion {
             ret: return_info,
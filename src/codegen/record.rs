@@ -1,9 +1,11 @@
-use super::{function, general, trait_impls};
+use super::{function, general, trait_impls, translate_from_glib::TranslateFromGlib};
 use crate::{
-    analysis::{self, special_functions::Type},
+    analysis::{self, conversion_type::ConversionType, special_functions::Type},
+    chunk::conversion_from_glib::Mode,
     env::Env,
-    library,
+    library, nameutil,
 };
+use log::warn;
 use std::io::{Result, Write};
 
 pub fn generate(w: &mut dyn Write, env: &Env, analysis: &analysis::record::Info) -> Result<()> {
@@ -12,7 +14,19 @@ pub fn generate(w: &mut dyn Write, env: &Env, analysis: &analysis::record::Info)
     general::start_comments(w, &env.config)?;
     general::uses(w, env, &analysis.imports)?;
 
-    if analysis.use_boxed_functions {
+    if analysis.is_direct {
+        general::define_direct_type(
+            w,
+            env,
+            &analysis.name,
+            &type_.c_type,
+            analysis.glib_get_type.as_ref().map(|(f, _)| f.as_str()),
+            &analysis.derives,
+        )?;
+        if analysis.derives.is_empty() {
+            generate_debug(w, analysis)?;
+        }
+    } else if analysis.use_boxed_functions {
         if let Some((ref glib_get_type, _)) = analysis.glib_get_type {
             general::define_auto_boxed_type(
                 w,
@@ -91,6 +105,16 @@ pub fn generate(w: &mut dyn Write, env: &Env, analysis: &analysis::record::Info)
         );
     }
 
+    generate_fields(
+        w,
+        env,
+        &analysis.name,
+        &type_.c_type,
+        &analysis.fields,
+        analysis.is_direct,
+        None,
+    )?;
+
     if analysis
         .functions
         .iter()
@@ -114,7 +138,9 @@ pub fn generate(w: &mut dyn Write, env: &Env, analysis: &analysis::record::Info)
         writeln!(w, "}}")?;
     }
 
-    general::declare_default_from_new(w, env, &analysis.name, &analysis.functions)?;
+    if !analysis.specials.has_trait(Type::Default) {
+        general::declare_default_from_new(w, env, &analysis.name, &analysis.functions)?;
+    }
 
     trait_impls::generate(
         w,
@@ -125,6 +151,10 @@ pub fn generate(w: &mut dyn Write, env: &Env, analysis: &analysis::record::Info)
         None,
     )?;
 
+    if analysis.is_direct && env.config.generate_serde(&analysis.full_name) {
+        generate_serde(w, env, analysis)?;
+    }
+
     if analysis.concurrency != library::Concurrency::None {
         writeln!(w)?;
     }
@@ -148,12 +178,15 @@ pub fn generate(w: &mut dyn Write, env: &Env, analysis: &analysis::record::Info)
 
 pub fn generate_reexports(
     env: &Env,
-    analysis: &analysis::record::Info,
+    analysis: &analysis::info_base::InfoBase,
     module_name: &str,
     contents: &mut Vec<String>,
 ) {
     let cfg_condition = general::cfg_condition_string(&analysis.cfg_condition, false, 0);
     let version_cfg = general::version_condition_string(env, analysis.version, false, 0);
+    let deprecated_version = env.library.type_(analysis.type_id).get_deprecated_version();
+    let deprecated_cfg =
+        general::deprecated_feature_condition_string(env, deprecated_version, false, 0);
     let mut cfg = String::new();
     if let Some(s) = cfg_condition {
         cfg.push_str(&s);
@@ -163,6 +196,10 @@ pub fn generate_reexports(
         cfg.push_str(&s);
         cfg.push('\n');
     };
+    if let Some(s) = deprecated_cfg {
+        cfg.push_str(&s);
+        cfg.push('\n');
+    };
     contents.push("".to_owned());
     contents.push(format!("{}mod {};", cfg, module_name));
     contents.push(format!(
@@ -170,3 +207,298 @@ pub fn generate_reexports(
         cfg, module_name, analysis.name
     ));
 }
+
+/// Implements `Debug` from the field getters.
+fn generate_debug(w: &mut dyn Write, analysis: &analysis::record::Info) -> Result<()> {
+    writeln!(w)?;
+    writeln!(w, "impl std::fmt::Debug for {} {{", analysis.name)?;
+    writeln!(
+        w,
+        "\tfn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {{"
+    )?;
+    writeln!(w, "\t\tf.debug_struct(\"{}\")", analysis.name)?;
+    for field in analysis.fields.iter().filter(|f| f.version.is_none()) {
+        if let Some(ref getter) = field.getter {
+            writeln!(w, "\t\t\t.field(\"{}\", &self.{}())", field.name, getter)?;
+        }
+    }
+    writeln!(w, "\t\t\t.finish()")?;
+    writeln!(w, "\t}}")?;
+    writeln!(w, "}}")
+}
+
+/// Implements serde traits through a struct with the same fields. Fields that aren't generated
+/// are zeroed when deserializing.
+fn generate_serde(w: &mut dyn Write, env: &Env, analysis: &analysis::record::Info) -> Result<()> {
+    if analysis.fields.is_empty() {
+        warn!(
+            "Can't implement serde traits for {}, its field accessors aren't generated",
+            analysis.full_name
+        );
+        return Ok(());
+    }
+    if analysis
+        .fields
+        .iter()
+        .any(|f| f.getter.is_none() || f.setter.is_none())
+    {
+        warn!(
+            "Can't implement serde traits for {}, some fields aren't both readable and writable",
+            analysis.full_name
+        );
+        return Ok(());
+    }
+
+    writeln!(w)?;
+    writeln!(w, "#[cfg(feature = \"serde\")]")?;
+    writeln!(w, "const _: () = {{")?;
+    writeln!(w, "	#[derive(serde::Serialize, serde::Deserialize)]")?;
+    writeln!(w, "	#[serde(rename = \"{}\")]", analysis.name)?;
+    writeln!(w, "	struct Fields {{")?;
+    for field in &analysis.fields {
+        let name = nameutil::mangle_keywords(&*field.name);
+        general::version_condition_no_doc(w, env, field.version, false, 2)?;
+        if name != field.name {
+            writeln!(w, "		#[serde(rename = \"{}\")]", field.name)?;
+        }
+        writeln!(w, "		{}: {},", name, field.rust_type)?;
+    }
+    writeln!(w, "	}}")?;
+    writeln!(w)?;
+
+    writeln!(w, "	impl serde::Serialize for {} {{", analysis.name)?;
+    writeln!(
+        w,
+        "		fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{"
+    )?;
+    writeln!(w, "			let fields = Fields {{")?;
+    for field in &analysis.fields {
+        general::version_condition_no_doc(w, env, field.version, false, 4)?;
+        writeln!(
+            w,
+            "\t\t\t\t{}: self.{}(),",
+            nameutil::mangle_keywords(&*field.name),
+            field.getter.as_ref().unwrap()
+        )?;
+    }
+    writeln!(w, "			}};")?;
+    writeln!(w, "			serde::Serialize::serialize(&fields, serializer)")?;
+    writeln!(w, "		}}")?;
+    writeln!(w, "	}}")?;
+    writeln!(w)?;
+
+    writeln!(
+        w,
+        "	impl<'de> serde::Deserialize<'de> for {} {{",
+        analysis.name
+    )?;
+    writeln!(
+        w,
+        "		fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{"
+    )?;
+    writeln!(
+        w,
+        "			let fields = <Fields as serde::Deserialize>::deserialize(deserializer)?;"
+    )?;
+    writeln!(w, "			let mut value = unsafe {{ Self::uninitialized() }};")?;
+    for field in &analysis.fields {
+        general::version_condition_no_doc(w, env, field.version, false, 3)?;
+        writeln!(
+            w,
+            "\t\t\tvalue.{}(fields.{});",
+            field.setter.as_ref().unwrap(),
+            nameutil::mangle_keywords(&*field.name)
+        )?;
+    }
+    writeln!(w, "			Ok(value)")?;
+    writeln!(w, "		}}")?;
+    writeln!(w, "	}}")?;
+    writeln!(w, "}};")
+}
+
+/// Generates getters and setters of the public fields of a record or union.
+///
+/// `union_tag` is `None` for records and contains the tag field, if any, for unions. Reading a
+/// union field is only safe for the tag and for fields with `tag_values`, which are checked
+/// against the tag and return an `Option`. The other getters are unsafe.
+///
+/// Setters of boxed types write through `to_glib_none_mut()`, so they take `&mut self` like the
+/// setters of direct types.
+pub fn generate_fields(
+    w: &mut dyn Write,
+    env: &Env,
+    type_name: &str,
+    c_type: &str,
+    fields: &[analysis::fields::Info],
+    is_direct: bool,
+    union_tag: Option<Option<&analysis::fields::Info>>,
+) -> Result<()> {
+    if fields.is_empty() {
+        return Ok(());
+    }
+
+    let access = if is_direct {
+        "self.0"
+    } else {
+        "(*self.as_ptr())"
+    };
+
+    writeln!(w)?;
+    writeln!(w, "impl {} {{", type_name)?;
+    for (i, field) in fields.iter().enumerate() {
+        if let Some(ref getter) = field.getter {
+            let value = if field.embedded {
+                format!("from_glib_none(&{}.{} as *const _)", access, field.name)
+            } else {
+                let mode = Mode {
+                    typ: field.typ,
+                    transfer: library::Transfer::None,
+                    is_uninitialized: false,
+                };
+                let (prefix, suffix) = mode.translate_from_glib_as_function(env, None);
+                format!("{}{}.{}{}", prefix, access, field.name, suffix)
+            };
+            let is_tag = matches!(union_tag, Some(Some(tag)) if tag.name == field.name);
+            let is_union_variant = union_tag.is_some() && !is_tag;
+            let is_safe = is_direct
+                && field.conversion_type == ConversionType::Direct
+                && !field.embedded
+                && !is_union_variant;
+
+            if i > 0 {
+                writeln!(w)?;
+            }
+            general::version_condition(w, env, field.version, false, 1)?;
+            match union_tag {
+                Some(Some(tag)) if is_union_variant && !field.tag_values.is_empty() => {
+                    writeln!(
+                        w,
+                        "\tpub fn {}(&self) -> Option<{}> {{",
+                        getter, field.rust_type
+                    )?;
+                    writeln!(
+                        w,
+                        "\t\tif matches!(self.{}(), {}) {{",
+                        tag.getter.as_ref().unwrap(),
+                        field.tag_values.join(" | ")
+                    )?;
+                    writeln!(w, "\t\t\tSome(unsafe {{ {} }})", value)?;
+                    writeln!(w, "\t\t}} else {{")?;
+                    writeln!(w, "\t\t\tNone")?;
+                    writeln!(w, "\t\t}}")?;
+                }
+                _ if is_union_variant => {
+                    writeln!(
+                        w,
+                        "\tpub unsafe fn {}(&self) -> {} {{",
+                        getter, field.rust_type
+                    )?;
+                    writeln!(w, "\t\t{}", value)?;
+                }
+                _ => {
+                    writeln!(w, "\tpub fn {}(&self) -> {} {{", getter, field.rust_type)?;
+                    if is_safe {
+                        writeln!(w, "\t\t{}", value)?;
+                    } else {
+                        writeln!(w, "\t\tunsafe {{ {} }}", value)?;
+                    }
+                }
+            }
+            writeln!(w, "\t}}")?;
+        }
+
+        if let Some(ref setter) = field.setter {
+            let par_name = nameutil::mangle_keywords(&*field.name);
+            let value = match field.conversion_type {
+                ConversionType::Direct => par_name.clone().into_owned(),
+                _ => format!("{}.to_glib()", par_name),
+            };
+            writeln!(w)?;
+            general::version_condition(w, env, field.version, false, 1)?;
+            writeln!(
+                w,
+                "\tpub fn {}(&mut self, {}: {}) {{",
+                setter, par_name, field.rust_type
+            )?;
+            if is_direct {
+                writeln!(w, "\t\tself.0.{} = {};", field.name, value)?;
+            } else {
+                writeln!(w, "\t\tunsafe {{")?;
+                writeln!(
+                    w,
+                    "\t\t\tlet ptr: *mut {}::{} = self.to_glib_none_mut().0;",
+                    env.main_sys_crate_name(),
+                    c_type
+                )?;
+                writeln!(w, "\t\t\t(*ptr).{} = {};", field.name, value)?;
+                writeln!(w, "\t\t}}")?;
+            }
+            writeln!(w, "\t}}")?;
+        }
+    }
+    writeln!(w, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::env::tests::env;
+
+    const POINT: &str = r#"
+    <record name="Point" c:type="TestPoint" glib:type-name="TestPoint" glib:get-type="test_point_get_type" c:symbol-prefix="point">
+      <field name="x" writable="1"><type name="gint" c:type="gint"/></field>
+      <field name="y" writable="1"><type name="gint" c:type="gint"/></field>
+    </record>
+"#;
+
+    #[test]
+    fn field_accessors_are_opt_in() {
+        let code = env(
+            POINT,
+            "[[object]]\nname = \"Test.Point\"\nstatus = \"generate\"\n",
+        )
+        .generate("point.rs");
+        assert!(!code.contains("pub fn x("));
+        assert!(!code.contains("pub fn set_x("));
+    }
+
+    #[test]
+    fn configured_field_accessors() {
+        let code = env(
+            POINT,
+            r#"
+[[object]]
+name = "Test.Point"
+status = "generate"
+    [[object.field]]
+    name = "y"
+"#,
+        )
+        .generate("point.rs");
+        assert!(!code.contains("pub fn x("));
+        assert!(code.contains("pub fn y(&self) -> i32 {"));
+        assert!(code.contains("unsafe { (*self.as_ptr()).y }"));
+        assert!(code.contains("pub fn set_y(&mut self, y: i32) {"));
+        assert!(code.contains("let ptr: *mut ffi::TestPoint = self.to_glib_none_mut().0;"));
+        assert!(code.contains("(*ptr).y = y;"));
+    }
+
+    #[test]
+    fn all_field_accessors() {
+        let code = env(
+            POINT,
+            r#"
+[[object]]
+name = "Test.Point"
+status = "generate"
+generate_fields = true
+    [[object.field]]
+    name = "y"
+    ignore = true
+"#,
+        )
+        .generate("point.rs");
+        assert!(code.contains("pub fn x(&self) -> i32 {"));
+        assert!(code.contains("pub fn set_x(&mut self, x: i32) {"));
+        assert!(!code.contains("pub fn y("));
+    }
+}
@@ -45,7 +45,16 @@ impl ToReturnValue for analysis::return_value::Info {
         match self.parameter {
             Some(ref par) => {
                 let name = par.to_return_value(env, is_trampoline);
-                if self.nullable_return_is_error.is_some() && name.starts_with(" -> Option<") {
+                if self.int_return_is_error.is_some() {
+                    format!(
+                        " -> Result<{}, {}>",
+                        &name[4..],
+                        use_glib_type(env, "BoolError")
+                    )
+                } else if self.int_return_is_none.is_some() {
+                    format!(" -> Option<{}>", &name[4..])
+                } else if self.nullable_return_is_error.is_some() && name.starts_with(" -> Option<")
+                {
                     // Change ` -> Option<T>` to ` -> Result<T, glib::BoolError>`
                     format!(
                         " -> Result<{}, {}BoolError>",
@@ -112,9 +121,9 @@ fn out_parameter_as_return_parts(
         Throws(..) => {
             if num_outs == 1 + 1 {
                 //if only one parameter except "glib::Error"
-                ("Result<", format!(", {}>", use_glib_type(env, "Error")))
+                ("Result<", format!(", {}>", analysis.error_type(env)))
             } else {
-                ("Result<(", format!("), {}>", use_glib_type(env, "Error")))
+                ("Result<(", format!("), {}>", analysis.error_type(env)))
             }
         }
         None => unreachable!(),
@@ -1,5 +1,5 @@
 use super::{
-    general::{cfg_deprecated, doc_hidden, version_condition},
+    general::{cfg_deprecated, deprecated_feature_condition, doc_hidden, version_condition},
     signal_body,
     trampoline::{self, func_string},
 };
@@ -34,6 +34,13 @@ pub fn generate(
     if !in_trait || only_declaration {
         cfg_deprecated(w, env, analysis.deprecated_version, commented, indent)?;
     }
+    deprecated_feature_condition(
+        w,
+        env,
+        analysis.deprecated_feature_version,
+        commented,
+        indent,
+    )?;
     version_condition(w, env, analysis.version, commented, indent)?;
     doc_hidden(w, analysis.doc_hidden, comment_prefix, indent)?;
     writeln!(
@@ -87,6 +94,13 @@ pub fn generate(
         if !in_trait || only_declaration {
             cfg_deprecated(w, env, analysis.deprecated_version, commented, indent)?;
         }
+        deprecated_feature_condition(
+            w,
+            env,
+            analysis.deprecated_feature_version,
+            commented,
+            indent,
+        )?;
         version_condition(w, env, analysis.version, commented, indent)?;
 
         let function_type = function_type_string(env, analysis, false);
@@ -5,7 +5,7 @@ use crate::{
     Env,
 };
 
-use super::general::version_condition;
+use super::general::{deprecated_feature_condition, version_condition};
 
 pub(super) fn generate(
     w: &mut dyn Write,
@@ -29,6 +29,7 @@ pub(super) fn generate_static_to_str(
     function: &analysis::functions::Info,
 ) -> Result<()> {
     writeln!(w)?;
+    deprecated_feature_condition(w, env, function.deprecated_feature_version, false, 1)?;
     version_condition(w, env, function.version, false, 1)?;
 
     let visibility = match function.visibility {
@@ -106,6 +106,9 @@ fn fill_in(root: &mut Table, env: &Env) {
             Some(version)
         });
         features.insert("dox".to_string(), Value::Array(Vec::new()));
+        if env.config.deprecated_feature {
+            features.insert("deprecated".to_string(), Value::Array(Vec::new()));
+        }
     }
 
     {
@@ -64,7 +64,7 @@ pub fn from_record(env: &Env, record: &Record) -> Fields {
         truncated,
         derives_copy,
         kind: "struct",
-        cfg_condition: get_gobject_cfg_condition(env, &record.name),
+        cfg_condition: get_gobject_cfg_condition(env, &record.name, &record.c_type),
         fields,
     }
 }
@@ -78,7 +78,7 @@ pub fn from_class(env: &Env, klass: &Class) -> Fields {
         truncated,
         derives_copy,
         kind: "struct",
-        cfg_condition: get_gobject_cfg_condition(env, &klass.name),
+        cfg_condition: get_gobject_cfg_condition(env, &klass.name, &klass.c_type),
         fields,
     }
 }
@@ -92,7 +92,10 @@ pub fn from_union(env: &Env, union: &Union) -> Fields {
         truncated,
         derives_copy,
         kind: "union",
-        cfg_condition: None,
+        cfg_condition: union
+            .c_type
+            .as_ref()
+            .and_then(|c_type| env.config.cfg_condition_by_prefix(c_type)),
         fields,
     }
 }
@@ -162,11 +165,11 @@ fn field_ffi_type(env: &Env, field: &Field) -> Result {
     }
 }
 
-fn get_gobject_cfg_condition(env: &Env, name: &str) -> Option<String> {
+fn get_gobject_cfg_condition(env: &Env, name: &str, c_type: &str) -> Option<String> {
     let full_name = format!("{}.{}", env.namespaces.main().name, name);
-    if let Some(obj) = env.config.objects.get(&full_name) {
-        obj.cfg_condition.clone()
-    } else {
-        None
-    }
+    env.config
+        .objects
+        .get(&full_name)
+        .and_then(|obj| obj.cfg_condition.clone())
+        .or_else(|| env.config.cfg_condition_by_prefix(c_type))
 }
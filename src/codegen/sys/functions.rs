@@ -175,13 +175,16 @@ pub fn generate_other_funcs(
 
 fn generate_cfg_configure(
     w: &mut dyn Write,
+    env: &Env,
     configured_functions: &[&Function],
+    c_identifier: &str,
     commented: bool,
 ) -> Result<()> {
     let cfg_condition_ = configured_functions
         .iter()
         .filter_map(|f| f.cfg_condition.clone())
-        .next();
+        .next()
+        .or_else(|| env.config.cfg_condition_by_prefix(c_identifier));
     cfg_condition(w, &cfg_condition_, commented, 1)?;
     Ok(())
 }
@@ -220,7 +223,7 @@ fn generate_object_funcs(
                 .max()
                 .flatten();
             version_condition(w, env, version, false, 1)?;
-            generate_cfg_configure(w, &configured_functions, false)?;
+            generate_cfg_configure(w, env, &configured_functions, glib_get_type, false)?;
             writeln!(w, "    pub fn {}() -> GType;", glib_get_type)?;
         }
     }
@@ -262,7 +265,7 @@ fn generate_object_funcs(
             writeln!(w, "    {}pub fn {}_utf8{};", comment, name, sig)?;
             version_condition(w, env, version, commented, 1)?;
         }
-        generate_cfg_configure(w, &configured_functions, commented)?;
+        generate_cfg_configure(w, env, &configured_functions, name, commented)?;
         writeln!(w, "    {}pub fn {}{};", comment, name, sig)?;
     }
 
@@ -216,13 +216,16 @@ fn generate_bitfields(w: &mut dyn Write, env: &Env, items: &[&Bitfield]) -> Resu
 
 fn generate_constant_cfg_configure(
     w: &mut dyn Write,
+    env: &Env,
     configured_constants: &[&constants::Constant],
+    c_identifier: &str,
     commented: bool,
 ) -> Result<()> {
     let cfg_condition_ = configured_constants
         .iter()
         .filter_map(|f| f.cfg_condition.clone())
-        .next();
+        .next()
+        .or_else(|| env.config.cfg_condition_by_prefix(c_identifier));
     cfg_condition(w, &cfg_condition_, commented, 1)?;
     Ok(())
 }
@@ -264,10 +267,16 @@ fn generate_constants(w: &mut dyn Write, env: &Env, constants: &[Constant]) -> R
             value = (val as u32).to_string();
         }
 
-        if let Some(obj) = config {
-            let configured_constants = obj.constants.matched(&full_name);
-            generate_constant_cfg_configure(w, &configured_constants, !comment.is_empty())?;
-        }
+        let configured_constants = config
+            .map(|obj| obj.constants.matched(&full_name))
+            .unwrap_or_default();
+        generate_constant_cfg_configure(
+            w,
+            env,
+            &configured_constants,
+            &constant.c_identifier,
+            !comment.is_empty(),
+        )?;
 
         writeln!(
             w,
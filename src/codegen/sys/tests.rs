@@ -99,7 +99,8 @@ fn prepare_ctype(env: &Env, ns: &Namespace, t: &Type) -> Option<CType> {
         .config
         .objects
         .get(&full_name)
-        .and_then(|obj| obj.cfg_condition.clone());
+        .and_then(|obj| obj.cfg_condition.clone())
+        .or_else(|| env.config.cfg_condition_by_prefix(name));
     Some(CType {
         name: name.to_owned(),
         cfg_condition,
@@ -1,9 +1,16 @@
-use super::general::version_condition;
+use super::general::{deprecated_feature_condition, version_condition};
 use crate::{
     analysis::{
+        function_parameters::RustParameter,
         functions::Info,
-        special_functions::{Infos, Type},
+        out_parameters::Mode,
+        ref_mode::RefMode,
+        rust_type::{is_string_type, parameter_rust_type},
+        special_functions::{self, FunctionType, Infos, Type},
     },
+    library::{self, Nullable},
+    nameutil::{use_gio_type, use_glib_type},
+    traits::*,
     Env,
 };
 use std::io::{Result, Write};
@@ -30,6 +37,29 @@ pub fn generate(
                 }
                 Type::Display => generate_display(w, env, type_name, info, trait_name)?,
                 Type::Hash => generate_hash(w, env, type_name, info, trait_name)?,
+                Type::Iterator => generate_iterator(
+                    w,
+                    env,
+                    type_name,
+                    info,
+                    specials.iterator_item(),
+                    trait_name,
+                )?,
+                Type::IntoIterator => generate_into_iterator(w, env, type_name, info, trait_name)?,
+                Type::FromStr => generate_from_str(w, env, type_name, info)?,
+                Type::Default => generate_default(w, env, type_name, info)?,
+                Type::Index => generate_index(w, env, type_name, info)?,
+                Type::AsRefStr => generate_as_ref_str(w, env, type_name, info, specials)?,
+                _ => {}
+            }
+        }
+    }
+    for (type_, special_info) in specials.repeatable_traits() {
+        if let Some(info) = lookup(functions, &special_info.glib_name) {
+            match *type_ {
+                Type::From => generate_from(w, env, type_name, info, false)?,
+                Type::TryFrom => generate_from(w, env, type_name, info, true)?,
+                Type::Extend => generate_extend(w, env, type_name, info, trait_name)?,
                 _ => {}
             }
         }
@@ -82,6 +112,7 @@ fn generate_display(
     trait_name: Option<&str>,
 ) -> Result<()> {
     writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
     version_condition(w, env, func.version, false, 0)?;
 
     use crate::analysis::out_parameters::Mode;
@@ -123,6 +154,7 @@ fn generate_hash(
     trait_name: Option<&str>,
 ) -> Result<()> {
     writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
     version_condition(w, env, func.version, false, 0)?;
 
     let call = generate_call(&func.name, &[], trait_name);
@@ -149,6 +181,7 @@ fn generate_eq(
     trait_name: Option<&str>,
 ) -> Result<()> {
     writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
     version_condition(w, env, func.version, false, 0)?;
 
     let call = generate_call(&func.name, &["other"], trait_name);
@@ -177,6 +210,7 @@ fn generate_eq_compare(
     trait_name: Option<&str>,
 ) -> Result<()> {
     writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
     version_condition(w, env, func.version, false, 0)?;
 
     let call = generate_call(&func.name, &["other"], trait_name);
@@ -205,6 +239,7 @@ fn generate_ord(
     trait_name: Option<&str>,
 ) -> Result<()> {
     writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
     version_condition(w, env, func.version, false, 0)?;
 
     let call = generate_call(&func.name, &["other"], trait_name);
@@ -229,3 +264,474 @@ impl Ord for {type_name} {{
         call = call
     )
 }
+
+/// Calls a function from inside a trait implementation, without resolving to the trait
+/// method of the same name. The instance is `this`, cancellables are `None` and the
+/// other parameters are taken from `args`.
+fn generate_qualified_call(
+    env: &Env,
+    type_name: &str,
+    func: &Info,
+    this: &str,
+    args: &[&str],
+    trait_name: Option<&str>,
+) -> String {
+    let mut args = args.iter();
+    let mut call_args = Vec::new();
+    for par in &func.parameters.rust_parameters {
+        if func.parameters.c_parameters[par.ind_c].instance_parameter {
+            call_args.push(this.to_owned());
+        } else if par.name == "cancellable" {
+            call_args.push(use_gio_type(env, "NONE_CANCELLABLE"));
+        } else if let Some(arg) = args.next() {
+            call_args.push((*arg).to_owned());
+        }
+    }
+    match trait_name {
+        Some(trait_name) if func.kind == library::FunctionKind::Method => format!(
+            "<{} as {}>::{}({})",
+            type_name,
+            trait_name,
+            func.codegen_name(),
+            call_args.join(", ")
+        ),
+        _ => format!(
+            "{}::{}({})",
+            type_name,
+            func.codegen_name(),
+            call_args.join(", ")
+        ),
+    }
+}
+
+fn non_nullable_rust_type(env: &Env, par: &library::Parameter) -> String {
+    parameter_rust_type(
+        env,
+        par.typ,
+        library::ParameterDirection::Return,
+        Nullable(false),
+        RefMode::None,
+        par.scope,
+    )
+    .into_string()
+}
+
+fn generate_iterator(
+    w: &mut dyn Write,
+    env: &Env,
+    type_name: &str,
+    func: &Info,
+    item: Option<&str>,
+    trait_name: Option<&str>,
+) -> Result<()> {
+    let par = match func.outs.mode {
+        Mode::None => func.ret.parameter.as_ref(),
+        _ => func.outs.params.first(),
+    };
+    let item = match (item, par) {
+        (Some(item), _) => item.to_owned(),
+        (None, Some(par)) => non_nullable_rust_type(env, par),
+        (None, None) => return Ok(()),
+    };
+
+    writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
+    version_condition(w, env, func.version, false, 0)?;
+
+    if let Mode::Throws(_) = func.outs.mode {
+        return generate_fallible_iterator(w, env, type_name, func, &item, trait_name);
+    }
+
+    let call = generate_qualified_call(env, type_name, func, "self", &[], trait_name);
+
+    writeln!(
+        w,
+        "\
+impl Iterator for {type_name} {{
+    type Item = {item};
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {{
+        {call}
+    }}
+}}",
+        type_name = type_name,
+        item = item,
+        call = call
+    )
+}
+
+/// Iterates over the items of a throwing `next` through a reference, as the type itself can't
+/// keep track of the errors: `Result<Option<T>, E>` becomes `Option<Result<T, E>>` and the
+/// iteration ends after the first error.
+fn generate_fallible_iterator(
+    w: &mut dyn Write,
+    env: &Env,
+    type_name: &str,
+    func: &Info,
+    item: &str,
+    trait_name: Option<&str>,
+) -> Result<()> {
+    let item = format!("Result<{}, {}>", item, func.error_type(env));
+    let call = generate_qualified_call(env, type_name, func, "this", &[], trait_name);
+
+    writeln!(
+        w,
+        "\
+impl<'a> IntoIterator for &'a {type_name} {{
+    type Item = {item};
+    type IntoIter = std::iter::Scan<
+        std::iter::Map<std::iter::Repeat<&'a {type_name}>, fn(&'a {type_name}) -> Option<{item}>>,
+        bool,
+        fn(&mut bool, Option<{item}>) -> Option<{item}>,
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {{
+        std::iter::repeat(self)
+            .map((|this: &'a {type_name}| {call}.transpose()) as fn(_) -> _)
+            .scan(false, |failed, item| {{
+                if *failed {{
+                    return None;
+                }}
+                *failed = matches!(item, Some(Err(_)));
+                item
+            }})
+    }}
+}}",
+        type_name = type_name,
+        item = item,
+        call = call
+    )
+}
+
+fn generate_into_iterator(
+    w: &mut dyn Write,
+    env: &Env,
+    type_name: &str,
+    func: &Info,
+    trait_name: Option<&str>,
+) -> Result<()> {
+    let iter = match func.ret.parameter {
+        Some(ref par) => non_nullable_rust_type(env, par),
+        None => return Ok(()),
+    };
+
+    writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
+    version_condition(w, env, func.version, false, 0)?;
+
+    let call = generate_qualified_call(env, type_name, func, "&self", &[], trait_name);
+
+    writeln!(
+        w,
+        "\
+impl IntoIterator for {type_name} {{
+    type Item = <{iter} as Iterator>::Item;
+    type IntoIter = {iter};
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {{
+        {call}
+    }}
+}}",
+        type_name = type_name,
+        iter = iter,
+        call = call
+    )
+}
+
+/// Returns the error type and the body of a function returning `Result<Self, Error>`
+fn generate_fallible_body(env: &Env, func: &Info, call: String) -> (String, String) {
+    if let Mode::Throws(_) = func.outs.mode {
+        (func.error_type(env), call)
+    } else if func.ret.bool_return_is_error.is_some()
+        || func.ret.nullable_return_is_error.is_some()
+        || func.ret.int_return_is_error.is_some()
+    {
+        (use_glib_type(env, "BoolError"), call)
+    } else if func.ret.int_return_is_none.is_some()
+        || matches!(func.ret.parameter, Some(ref ret) if *ret.nullable)
+    {
+        (
+            use_glib_type(env, "BoolError"),
+            format!(
+                "{}.ok_or_else(|| {}(\"{} failed\"))",
+                call,
+                use_glib_type(env, "bool_error!"),
+                func.glib_name
+            ),
+        )
+    } else {
+        (
+            "std::convert::Infallible".to_owned(),
+            format!("Ok({})", call),
+        )
+    }
+}
+
+/// Returns the type taken by a `From`-like implementation for a parameter, the
+/// pattern binding it and the expression passing it to the function.
+fn conversion_argument(env: &Env, func: &Info, par: &RustParameter) -> (String, String, String) {
+    let c_par = &func.parameters.c_parameters[par.ind_c];
+    let (typ, binding, arg) = if is_string_type(env, par.typ) {
+        ("&str".to_owned(), "v", "v")
+    } else {
+        let typ = parameter_rust_type(
+            env,
+            par.typ,
+            library::ParameterDirection::Return,
+            Nullable(false),
+            RefMode::None,
+            c_par.scope,
+        )
+        .into_string();
+        match c_par.ref_mode {
+            RefMode::ByRefMut => (typ, "mut v", "&mut v"),
+            ref_mode if ref_mode.is_ref() => (typ, "v", "&v"),
+            _ => (typ, "v", "v"),
+        }
+    };
+    let arg = if par.allow_none {
+        format!("Some({})", arg)
+    } else {
+        arg.to_owned()
+    };
+    (typ, binding.to_owned(), arg)
+}
+
+fn generate_from_str(w: &mut dyn Write, env: &Env, type_name: &str, func: &Info) -> Result<()> {
+    let arg = if func.parameters.rust_parameters[0].allow_none {
+        "Some(s)"
+    } else {
+        "s"
+    };
+    let call = generate_qualified_call(env, type_name, func, "", &[arg], None);
+    let (err, body) = generate_fallible_body(env, func, call);
+
+    writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
+    version_condition(w, env, func.version, false, 0)?;
+
+    writeln!(
+        w,
+        "\
+impl str::FromStr for {type_name} {{
+    type Err = {err};
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {{
+        {body}
+    }}
+}}",
+        type_name = type_name,
+        err = err,
+        body = body
+    )
+}
+
+fn generate_default(w: &mut dyn Write, env: &Env, type_name: &str, func: &Info) -> Result<()> {
+    let call = generate_qualified_call(env, type_name, func, "", &[], None);
+
+    writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
+    version_condition(w, env, func.version, false, 0)?;
+
+    writeln!(
+        w,
+        "\
+impl Default for {type_name} {{
+    fn default() -> Self {{
+        {call}
+    }}
+}}",
+        type_name = type_name,
+        call = call
+    )
+}
+
+fn generate_from(
+    w: &mut dyn Write,
+    env: &Env,
+    type_name: &str,
+    func: &Info,
+    fallible: bool,
+) -> Result<()> {
+    let par = special_functions::arguments(func)[0];
+    let (typ, binding, arg) = conversion_argument(env, func, par);
+    let call = generate_qualified_call(env, type_name, func, "", &[&arg], None);
+
+    writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
+    version_condition(w, env, func.version, false, 0)?;
+
+    if fallible {
+        let (err, body) = generate_fallible_body(env, func, call);
+        writeln!(
+            w,
+            "\
+impl TryFrom<{typ}> for {type_name} {{
+    type Error = {err};
+
+    fn try_from({binding}: {typ}) -> Result<Self, Self::Error> {{
+        {body}
+    }}
+}}",
+            type_name = type_name,
+            typ = typ,
+            binding = binding,
+            err = err,
+            body = body
+        )
+    } else {
+        writeln!(
+            w,
+            "\
+impl From<{typ}> for {type_name} {{
+    fn from({binding}: {typ}) -> Self {{
+        {call}
+    }}
+}}",
+            type_name = type_name,
+            typ = typ,
+            binding = binding,
+            call = call
+        )
+    }
+}
+
+fn generate_extend(
+    w: &mut dyn Write,
+    env: &Env,
+    type_name: &str,
+    func: &Info,
+    trait_name: Option<&str>,
+) -> Result<()> {
+    let par = special_functions::arguments(func)[0];
+    let (typ, binding, arg) = conversion_argument(env, func, par);
+    let call = generate_qualified_call(env, type_name, func, "self", &[&arg], trait_name);
+    let returns_unit = func.outs.is_empty()
+        && !matches!(
+            func.ret.parameter,
+            Some(ref ret) if ret.typ != library::TypeId::tid_none()
+        );
+    let call = if returns_unit {
+        call
+    } else {
+        format!("let _ = {}", call)
+    };
+    // References in the item type need a named lifetime
+    let (generics, typ) = match typ.strip_prefix('&') {
+        Some(typ) => ("<'a>", format!("&'a {}", typ)),
+        None => ("", typ),
+    };
+
+    writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
+    version_condition(w, env, func.version, false, 0)?;
+
+    writeln!(
+        w,
+        "\
+impl{generics} Extend<{typ}> for {type_name} {{
+    fn extend<I: IntoIterator<Item = {typ}>>(&mut self, iter: I) {{
+        for {binding} in iter {{
+            {call};
+        }}
+    }}
+}}",
+        generics = generics,
+        type_name = type_name,
+        typ = typ,
+        binding = binding,
+        call = call
+    )
+}
+
+/// The returned pointer is borrowed from `self`, so only types that can be
+/// referenced in place (direct records) can be the output of `Index`.
+fn generate_index(w: &mut dyn Write, env: &Env, type_name: &str, func: &Info) -> Result<()> {
+    let ret = match func.ret.parameter {
+        Some(ref ret) => ret,
+        None => return Ok(()),
+    };
+    let par = special_functions::arguments(func)[0];
+    let output = non_nullable_rust_type(env, ret);
+    let index = parameter_rust_type(
+        env,
+        par.typ,
+        library::ParameterDirection::In,
+        Nullable(false),
+        RefMode::None,
+        ret.scope,
+    )
+    .into_string();
+
+    writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
+    version_condition(w, env, func.version, false, 0)?;
+
+    writeln!(
+        w,
+        "\
+impl ops::Index<{index}> for {type_name} {{
+    type Output = {output};
+
+    fn index(&self, index: {index}) -> &Self::Output {{
+        unsafe {{
+            let ptr = {sys_crate_name}::{glib_name}(self.to_glib_none().0, index);
+            assert!(!ptr.is_null(), \"index out of bounds\");
+            &*(ptr as *const Self::Output)
+        }}
+    }}
+}}",
+        index = index,
+        type_name = type_name,
+        output = output,
+        sys_crate_name = env.main_sys_crate_name(),
+        glib_name = func.glib_name
+    )
+}
+
+fn generate_as_ref_str(
+    w: &mut dyn Write,
+    env: &Env,
+    type_name: &str,
+    func: &Info,
+    specials: &Infos,
+) -> Result<()> {
+    let is_static = matches!(
+        specials.functions().get(&func.glib_name),
+        Some(info) if info.type_ == FunctionType::StaticStringify
+    );
+    let body = if is_static {
+        format!("self.{}()", func.codegen_name())
+    } else {
+        // The string is owned by `self`, checked in the analysis, and `utf8` strings are
+        // UTF-8 by the introspection annotation
+        format!(
+            "\
+unsafe {{
+            std::ffi::CStr::from_ptr({}::{}(self.to_glib_none().0))
+                .to_str()
+                .expect(\"utf8 string\")
+        }}",
+            env.main_sys_crate_name(),
+            func.glib_name
+        )
+    };
+
+    writeln!(w)?;
+    deprecated_feature_condition(w, env, func.deprecated_feature_version, false, 0)?;
+    version_condition(w, env, func.version, false, 0)?;
+
+    writeln!(
+        w,
+        "\
+impl AsRef<str> for {type_name} {{
+    fn as_ref(&self) -> &str {{
+        {body}
+    }}
+}}",
+        type_name = type_name,
+        body = body
+    )
+}
@@ -142,7 +142,7 @@ fn func_parameter(
     };
 
     match bounds.get_parameter_alias_info(&par.name) {
-        Some((t, bound_type)) => match bound_type {
+        Some((Some(t), bound_type)) => match bound_type {
             BoundType::NoWrapper => unreachable!(),
             BoundType::IsA(_) => {
                 if *par.nullable {
@@ -159,7 +159,7 @@ fn func_parameter(
             }
             BoundType::AsRef(_) => t.to_string(),
         },
-        None => {
+        _ => {
             let rust_type = parameter_rust_type(
                 env,
                 par.typ,
@@ -1,5 +1,9 @@
 use crate::{
-    analysis::{self, conversion_type::ConversionType, rust_type::rust_type},
+    analysis::{
+        self,
+        conversion_type::{is_direct_fixed_array, ConversionType},
+        rust_type::rust_type,
+    },
     chunk::conversion_from_glib::Mode,
     env::Env,
     library,
@@ -24,6 +28,8 @@ impl TranslateFromGlib for Mode {
         use crate::analysis::conversion_type::ConversionType::*;
         match ConversionType::of(env, self.typ) {
             Direct => (String::new(), String::new()),
+            // Caller-allocated out arrays are filled in place
+            Pointer if is_direct_fixed_array(env, self.typ) => (String::new(), String::new()),
             Scalar => match env.library.type_(self.typ) {
                 library::Type::Fundamental(library::Fundamental::UniChar) => (
                     "std::convert::TryFrom::try_from(".into(),
@@ -37,7 +43,8 @@ impl TranslateFromGlib for Mode {
                     library::Type::List(..)
                     | library::Type::SList(..)
                     | library::Type::PtrArray(..)
-                    | library::Type::CArray(..) => {
+                    | library::Type::CArray(..)
+                    | library::Type::HashTable(..) => {
                         if array_length.is_some() {
                             (format!("FromGlibContainer::{}", trans.0), trans.1)
                         } else {
@@ -113,6 +120,43 @@ impl TranslateFromGlib for analysis::return_value::Info {
                         res
                     }
                 }
+                // Negative sentinels are out of the range of the unsigned type returned, like
+                // all the other negative values
+                None if self.int_return_is_error.is_some() => {
+                    let int_return = self.int_return_is_error.as_ref().unwrap();
+                    let error = format!(
+                        "{}(\"{}\")",
+                        use_glib_type(env, "bool_error!"),
+                        int_return.message
+                    );
+                    if int_return.value < 0 {
+                        (
+                            "std::convert::TryFrom::try_from(".into(),
+                            format!(").map_err(|_| {})", error),
+                        )
+                    } else {
+                        (
+                            "match ".into(),
+                            format!(
+                                " {{ {} => Err({}), ret => Ok(ret) }}",
+                                int_return.value, error
+                            ),
+                        )
+                    }
+                }
+                None if self.int_return_is_none.is_some() => {
+                    let value = self.int_return_is_none.unwrap();
+                    if value < 0 {
+                        ("std::convert::TryFrom::try_from(".into(), ").ok()".into())
+                    } else {
+                        (
+                            "match ".into(),
+                            format!(" {{ {} => None, ret => Some(ret) }}", value),
+                        )
+                    }
+                }
+                // The function returns a pointer to the array
+                None if is_direct_fixed_array(env, par.typ) => fixed_array_from_glib(env, par),
                 None => Mode::from(par).translate_from_glib_as_function(env, array_length),
             },
             None => (String::new(), ";".into()),
@@ -120,6 +164,28 @@ impl TranslateFromGlib for analysis::return_value::Info {
     }
 }
 
+/// Copies the array a returned pointer points to, freeing the pointer when owned.
+fn fixed_array_from_glib(env: &Env, par: &library::Parameter) -> (String, String) {
+    let array_type = rust_type(env, par.typ).into_string();
+    let (prefix, suffix) = if *par.nullable {
+        ("(", format!(" as *const {}).as_ref().copied()", array_type))
+    } else {
+        ("*(", format!(" as *const {})", array_type))
+    };
+    if par.transfer == library::Transfer::None {
+        return (prefix.into(), suffix);
+    }
+    (
+        "{ let ptr = ".into(),
+        format!(
+            "; let ret = {}ptr{}; {}(ptr as *mut _); ret }}",
+            prefix,
+            suffix,
+            use_glib_type(env, "ffi::g_free")
+        ),
+    )
+}
+
 fn from_glib_xxx(transfer: library::Transfer, array_length: Option<&String>) -> (String, String) {
     use crate::library::Transfer;
     let good_print = |name: &str| format!(", {}.assume_init() as usize)", name);
@@ -139,3 +205,190 @@ fn from_glib_xxx(transfer: library::Transfer, array_length: Option<&String>) ->
         ),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::env::tests::env;
+
+    const ARRAYS: &str = r#"
+    <function name="get_color" c:identifier="test_get_color">
+      <return-value transfer-ownership="none"><array zero-terminated="0" fixed-size="4" c:type="const guint8*"><type name="guint8" c:type="guint8"/></array></return-value>
+    </function>
+    <function name="dup_matrix" c:identifier="test_dup_matrix">
+      <return-value transfer-ownership="full" nullable="1"><array zero-terminated="0" fixed-size="6" c:type="gdouble*"><type name="gdouble" c:type="gdouble"/></array></return-value>
+    </function>
+    <function name="dup_color" c:identifier="test_dup_color">
+      <return-value transfer-ownership="full"><array zero-terminated="0" fixed-size="4" c:type="guint8*"><type name="guint8" c:type="guint8"/></array></return-value>
+    </function>
+    <function name="find_color" c:identifier="test_find_color">
+      <return-value transfer-ownership="none" nullable="1"><array zero-terminated="0" fixed-size="4" c:type="const guint8*"><type name="guint8" c:type="guint8"/></array></return-value>
+    </function>
+    <function name="set_color" c:identifier="test_set_color">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="color" transfer-ownership="none"><array zero-terminated="0" fixed-size="4" c:type="const guint8*"><type name="guint8" c:type="guint8"/></array></parameter>
+      </parameters>
+    </function>
+    <function name="get_matrix" c:identifier="test_get_matrix">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="matrix" direction="out" caller-allocates="1" transfer-ownership="none"><array zero-terminated="0" fixed-size="6" c:type="gdouble*"><type name="gdouble" c:type="gdouble"/></array></parameter>
+      </parameters>
+    </function>
+    <function name="set_matrix" c:identifier="test_set_matrix">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="matrix" transfer-ownership="none" nullable="1" allow-none="1"><array zero-terminated="0" fixed-size="6" c:type="const gdouble*"><type name="gdouble" c:type="gdouble"/></array></parameter>
+      </parameters>
+    </function>
+"#;
+    const CONFIG: &str = r#"
+[[object]]
+name = "Test.*"
+status = "generate"
+    [[object.function]]
+    name = "get_color"
+        [object.function.return]
+        nullable = false
+    [[object.function]]
+    name = "dup_color"
+        [object.function.return]
+        nullable = false
+"#;
+
+    #[test]
+    fn fixed_array_returns() {
+        let code = env(ARRAYS, CONFIG).generate("functions.rs");
+        assert!(code.contains("pub fn get_color() -> [u8; 4] {"));
+        assert!(code.contains("*(ffi::test_get_color() as *const [u8; 4])"));
+        assert!(code.contains("pub fn find_color() -> Option<[u8; 4]> {"));
+        assert!(code.contains("(ffi::test_find_color() as *const [u8; 4]).as_ref().copied()"));
+        // Owned arrays are copied before being freed
+        assert!(code.contains("pub fn dup_color() -> [u8; 4] {"));
+        assert!(code.contains(
+            "{ let ptr = ffi::test_dup_color(); let ret = *(ptr as *const [u8; 4]); glib::ffi::g_free(ptr as *mut _); ret }"
+        ));
+        assert!(code.contains("pub fn dup_matrix() -> Option<[f64; 6]> {"));
+        assert!(code.contains(
+            "{ let ptr = ffi::test_dup_matrix(); let ret = (ptr as *const [f64; 6]).as_ref().copied(); glib::ffi::g_free(ptr as *mut _); ret }"
+        ));
+    }
+
+    #[test]
+    fn fixed_array_parameters() {
+        let code = env(ARRAYS, CONFIG).generate("functions.rs");
+        assert!(code.contains("pub fn set_color(color: &[u8; 4]) {"));
+        assert!(code.contains("ffi::test_set_color(color as *const _ as _);"));
+        assert!(code.contains("pub fn set_matrix(matrix: Option<&[f64; 6]>) {"));
+        assert!(code.contains(
+            "ffi::test_set_matrix(matrix.map_or(std::ptr::null(), |a| a as *const _) as _);"
+        ));
+        assert!(code.contains("pub fn get_matrix() -> [f64; 6] {"));
+        assert!(code.contains("ffi::test_get_matrix(matrix.as_mut_ptr() as *mut _);"));
+        assert!(code.contains("let matrix = matrix.assume_init();"));
+    }
+
+    const HASH_TABLES: &str = r#"
+    <function name="set_options" c:identifier="test_set_options">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="options" transfer-ownership="none"><type name="GLib.HashTable" c:type="GHashTable*"><type name="utf8"/><type name="utf8"/></type></parameter>
+      </parameters>
+    </function>
+    <function name="dup_options" c:identifier="test_dup_options">
+      <return-value transfer-ownership="full"><type name="GLib.HashTable" c:type="GHashTable*"><type name="utf8"/><type name="utf8"/></type></return-value>
+    </function>
+    <function name="get_options" c:identifier="test_get_options">
+      <return-value transfer-ownership="container"><type name="GLib.HashTable" c:type="GHashTable*"><type name="utf8"/><type name="utf8"/></type></return-value>
+    </function>
+    <function name="get_counts" c:identifier="test_get_counts">
+      <return-value transfer-ownership="full"><type name="GLib.HashTable" c:type="GHashTable*"><type name="utf8"/><type name="gint"/></type></return-value>
+    </function>
+    <function name="set_counts" c:identifier="test_set_counts">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="counts" transfer-ownership="none"><type name="GLib.HashTable" c:type="GHashTable*"><type name="gint"/><type name="utf8"/></type></parameter>
+      </parameters>
+    </function>
+"#;
+
+    #[test]
+    fn string_hash_tables() {
+        let code = env(HASH_TABLES, CONFIG).generate("functions.rs");
+        assert!(code.contains("use std::collections::HashMap;"));
+        assert!(code.contains("pub fn set_options(options: &HashMap<String, String>) {"));
+        assert!(code.contains("ffi::test_set_options(options.to_glib_none().0);"));
+        assert!(code.contains("pub fn dup_options() -> HashMap<String, String> {"));
+        assert!(code.contains("FromGlibPtrContainer::from_glib_full(ffi::test_dup_options())"));
+        assert!(code.contains("pub fn get_options() -> HashMap<String, String> {"));
+        assert!(code.contains("FromGlibPtrContainer::from_glib_container(ffi::test_get_options())"));
+    }
+
+    #[test]
+    fn unsupported_hash_tables_are_skipped() {
+        let code = env(HASH_TABLES, CONFIG).generate("functions.rs");
+        assert!(code.contains("//pub fn get_counts("));
+        assert!(code.contains("//pub fn set_counts("));
+        assert!(!code.contains("\npub fn get_counts("));
+        assert!(!code.contains("\npub fn set_counts("));
+    }
+
+    const INT_SENTINELS: &str = r#"
+    <function name="open_file" c:identifier="test_open_file">
+      <return-value transfer-ownership="none"><type name="gint" c:type="int"/></return-value>
+    </function>
+    <function name="find_index" c:identifier="test_find_index">
+      <return-value transfer-ownership="none"><type name="gint" c:type="int"/></return-value>
+    </function>
+    <function name="get_count" c:identifier="test_get_count">
+      <return-value transfer-ownership="none"><type name="guint" c:type="guint"/></return-value>
+    </function>
+    <function name="get_id" c:identifier="test_get_id">
+      <return-value transfer-ownership="none"><type name="guint" c:type="guint"/></return-value>
+    </function>
+"#;
+    const INT_SENTINELS_CONFIG: &str = r#"
+[[object]]
+name = "Test.*"
+status = "generate"
+    [[object.function]]
+    name = "open_file"
+        [object.function.return]
+        int_return_is_error = { value = -1, message = "Failed to open" }
+    [[object.function]]
+    name = "find_index"
+        [object.function.return]
+        int_return_is_none = -1
+    [[object.function]]
+    name = "get_count"
+        [object.function.return]
+        int_return_is_error = { value = 0, message = "No items" }
+    [[object.function]]
+    name = "get_id"
+        [object.function.return]
+        int_return_is_none = 0
+"#;
+
+    #[test]
+    fn negative_int_sentinels() {
+        let code = env(INT_SENTINELS, INT_SENTINELS_CONFIG).generate("functions.rs");
+        // All the negative values are out of range, not only the sentinel
+        assert!(code.contains("pub fn open_file() -> Result<u32, glib::BoolError> {"));
+        assert!(code.contains(
+            "std::convert::TryFrom::try_from(ffi::test_open_file()).map_err(|_| glib::bool_error!(\"Failed to open\"))"
+        ));
+        assert!(code.contains("pub fn find_index() -> Option<u32> {"));
+        assert!(code.contains("std::convert::TryFrom::try_from(ffi::test_find_index()).ok()"));
+    }
+
+    #[test]
+    fn int_sentinels() {
+        let code = env(INT_SENTINELS, INT_SENTINELS_CONFIG).generate("functions.rs");
+        assert!(code.contains("pub fn get_count() -> Result<u32, glib::BoolError> {"));
+        assert!(code.contains(
+            "match ffi::test_get_count() { 0 => Err(glib::bool_error!(\"No items\")), ret => Ok(ret) }"
+        ));
+        assert!(code.contains("pub fn get_id() -> Option<u32> {"));
+        assert!(code.contains("match ffi::test_get_id() { 0 => None, ret => Some(ret) }"));
+    }
+}
@@ -47,6 +47,7 @@ impl TranslateToGlib for TransformationType {
             ToGlibUnknown { ref name } => format!("/*Unknown conversion*/{}", name),
             ToSome(ref name) => format!("Some({})", name),
             IntoRaw(ref name) => format!("Box_::into_raw({}) as *mut _", name),
+            AsMutPtr(ref name) => format!("{}.as_mut_ptr() as *mut _", name),
             _ => unreachable!("Unexpected transformation type {:?}", self),
         }
     }
@@ -82,3 +83,43 @@ fn to_glib_xxx(
         Container => ("".into(), ".to_glib_container().0"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::env::tests::env;
+
+    const BUFFERS: &str = r#"
+    <function name="read_bytes" c:identifier="test_read_bytes">
+      <return-value transfer-ownership="none"><type name="gsize" c:type="gsize"/></return-value>
+      <parameters>
+        <parameter name="buffer" direction="out" caller-allocates="1" transfer-ownership="none"><array length="1" zero-terminated="0" c:type="guint8*"><type name="guint8" c:type="guint8"/></array></parameter>
+        <parameter name="count" transfer-ownership="none"><type name="gsize" c:type="gsize"/></parameter>
+      </parameters>
+    </function>
+    <function name="fill_bytes" c:identifier="test_fill_bytes">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="buffer" direction="out" caller-allocates="1" transfer-ownership="none"><array length="1" zero-terminated="0" c:type="guint8*"><type name="guint8" c:type="guint8"/></array></parameter>
+        <parameter name="count" transfer-ownership="none"><type name="gsize" c:type="gsize"/></parameter>
+      </parameters>
+    </function>
+"#;
+    const CONFIG: &str = r#"
+[[object]]
+name = "Test.*"
+status = "generate"
+    [[object.function]]
+    name = "read_bytes"
+    caller_allocated_buffers = true
+"#;
+
+    #[test]
+    fn caller_allocated_buffers() {
+        let code = env(BUFFERS, CONFIG).generate("functions.rs");
+        assert!(code.contains("pub fn read_bytes(buffer: &mut [u8]) -> usize {"));
+        assert!(code.contains("let count = buffer.len() as usize;"));
+        assert!(code.contains("ffi::test_read_bytes(buffer.as_mut_ptr() as *mut _, count)"));
+        // Only the functions opting in take a buffer
+        assert!(code.contains("pub fn fill_bytes() -> Vec<u8> {"));
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{
+    analysis::imports::Imports, codegen::general, env::Env, file_saver, nameutil::use_glib_type,
+};
+use std::path::Path;
+
+/// Generates `TypedError`, the error type of functions with a configured error domain.
+pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
+    let has_error_domains = env.config.objects.values().any(|obj| {
+        obj.error_domain.is_some() || obj.functions.iter().any(|f| f.error_domain.is_some())
+    });
+    if !has_error_domains {
+        return;
+    }
+
+    let path = root_path.join("typed_error.rs");
+    let mut imports = Imports::new(&env.library);
+    imports.add(&use_glib_type(env, "error::ErrorDomain"));
+    imports.add(&use_glib_type(env, "translate::*"));
+    imports.add("std::fmt");
+    imports.add("std::marker::PhantomData");
+
+    file_saver::save_to_file(path, env.config.make_backup, |w| {
+        general::start_comments(w, &env.config)?;
+        general::uses(w, env, &imports)?;
+        writeln!(w)?;
+
+        mod_rs.push("\nmod typed_error;".into());
+        mod_rs.push("pub use self::typed_error::TypedError;".into());
+
+        writeln!(
+            w,
+            "\
+/// Error of a function known to fail with errors of the domain `T`.
+///
+/// Errors of other domains are still available with [`error`](Self::error).
+#[derive(Debug)]
+pub struct TypedError<T> {{
+    error: {error},
+    domain: PhantomData<T>,
+}}
+
+impl<T: ErrorDomain> TypedError<T> {{
+    /// Returns the error code if the error belongs to the domain `T`.
+    pub fn kind(&self) -> Option<T> {{
+        self.error.kind::<T>()
+    }}
+
+    /// Returns the underlying error.
+    pub fn error(&self) -> &{error} {{
+        &self.error
+    }}
+
+    /// Converts into the underlying error.
+    pub fn into_error(self) -> {error} {{
+        self.error
+    }}
+}}
+
+impl<T> From<{error}> for TypedError<T> {{
+    fn from(error: {error}) -> Self {{
+        TypedError {{
+            error,
+            domain: PhantomData,
+        }}
+    }}
+}}
+
+impl<T> From<TypedError<T>> for {error} {{
+    fn from(error: TypedError<T>) -> Self {{
+        error.error
+    }}
+}}
+
+impl<T> fmt::Display for TypedError<T> {{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {{
+        fmt::Display::fmt(&self.error, f)
+    }}
+}}
+
+impl<T: fmt::Debug> std::error::Error for TypedError<T> {{}}
+
+#[doc(hidden)]
+impl<T> FromGlibPtrFull<*mut {ffi_error}> for TypedError<T> {{
+    unsafe fn from_glib_full(ptr: *mut {ffi_error}) -> Self {{
+        from_glib_full::<_, {error}>(ptr).into()
+    }}
+}}",
+            error = use_glib_type(env, "Error"),
+            ffi_error = use_glib_type(env, "ffi::GError"),
+        )
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::env::tests::env;
+
+    const LOADER: &str = r#"
+    <enumeration name="LoaderError" c:type="TestLoaderError" glib:error-domain="test-loader-error-quark">
+      <member name="failed" value="0" c:identifier="TEST_LOADER_ERROR_FAILED" glib:nick="failed"/>
+    </enumeration>
+    <class name="Loader" c:symbol-prefix="loader" c:type="TestLoader" parent="GObject.Object" glib:type-name="TestLoader" glib:get-type="test_loader_get_type">
+      <method name="load" c:identifier="test_loader_load" throws="1">
+        <return-value transfer-ownership="none"><type name="gboolean" c:type="gboolean"/></return-value>
+        <parameters>
+          <instance-parameter name="loader" transfer-ownership="none"><type name="Loader" c:type="TestLoader*"/></instance-parameter>
+        </parameters>
+      </method>
+      <method name="read_name" c:identifier="test_loader_read_name" throws="1">
+        <return-value transfer-ownership="full"><type name="utf8" c:type="gchar*"/></return-value>
+        <parameters>
+          <instance-parameter name="loader" transfer-ownership="none"><type name="Loader" c:type="TestLoader*"/></instance-parameter>
+        </parameters>
+      </method>
+    </class>
+"#;
+
+    const CONFIG: &str = r#"
+[[object]]
+name = "Test.LoaderError"
+status = "generate"
+
+[[object]]
+name = "Test.Loader"
+status = "generate"
+    [[object.function]]
+    name = "load"
+    error_domain = "LoaderError"
+"#;
+
+    #[test]
+    fn typed_error_signature() {
+        let code = env(LOADER, CONFIG).generate("loader.rs");
+        assert!(code.contains("use crate::TypedError;"));
+        assert!(code.contains("use crate::LoaderError;"));
+        assert!(code.contains("    pub fn load(&self) -> Result<(), TypedError<LoaderError>> {"));
+        // Only the configured function
+        assert!(
+            code.contains("    pub fn read_name(&self) -> Result<glib::GString, glib::Error> {")
+        );
+    }
+
+    #[test]
+    fn typed_error_conversion() {
+        let t = env(LOADER, CONFIG);
+        let code = t.generate("loader.rs");
+        // The error is converted through `FromGlibPtrFull` for `TypedError`
+        assert!(code.contains(
+            "            let _ = ffi::test_loader_load(self.to_glib_none().0, &mut error);\n            if error.is_null() { Ok(()) } else { Err(from_glib_full(error)) }"
+        ));
+        let code = t.generate("typed_error.rs");
+        assert!(code.contains(
+            "impl<T> FromGlibPtrFull<*mut glib::ffi::GError> for TypedError<T> {\n    unsafe fn from_glib_full(ptr: *mut glib::ffi::GError) -> Self {\n        from_glib_full::<_, glib::Error>(ptr).into()\n    }\n}"
+        ));
+    }
+
+    #[test]
+    fn typed_error_module() {
+        let t = env(LOADER, CONFIG);
+        let code = t.generate("typed_error.rs");
+        assert!(code.contains("use glib::error::ErrorDomain;"));
+        assert!(code.contains(
+            "pub struct TypedError<T> {\n    error: glib::Error,\n    domain: PhantomData<T>,\n}"
+        ));
+        assert!(code.contains(
+            "    pub fn kind(&self) -> Option<T> {\n        self.error.kind::<T>()\n    }"
+        ));
+        assert!(code.contains("impl<T> From<TypedError<T>> for glib::Error {"));
+        let code = t.generate("mod.rs");
+        assert!(code.contains("\nmod typed_error;\npub use self::typed_error::TypedError;\n"));
+    }
+
+    #[test]
+    fn no_typed_error_module_without_domains() {
+        let t = env(
+            LOADER,
+            "[[object]]\nname = \"Test.Loader\"\nstatus = \"generate\"\n",
+        );
+        let code = t.generate("loader.rs");
+        assert!(code.contains("    pub fn load(&self) -> Result<(), glib::Error> {"));
+        assert!(!t.env.config.auto_path.join("typed_error.rs").exists());
+    }
+}
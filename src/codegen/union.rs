@@ -0,0 +1,228 @@
+use super::{function, general, record, trait_impls};
+use crate::{analysis, analysis::special_functions::Type, env::Env, library};
+use std::io::{Result, Write};
+
+pub fn generate(w: &mut dyn Write, env: &Env, analysis: &analysis::union::Info) -> Result<()> {
+    let c_type = analysis.c_type(&env.library);
+    let glib_get_type = analysis.glib_get_type.as_ref().map(|(f, v)| {
+        if v > &analysis.version {
+            (f.clone(), *v)
+        } else {
+            (f.clone(), None)
+        }
+    });
+
+    general::start_comments(w, &env.config)?;
+    general::uses(w, env, &analysis.imports)?;
+
+    if analysis.is_direct {
+        general::define_direct_type(
+            w,
+            env,
+            &analysis.name,
+            c_type,
+            glib_get_type.as_ref().map(|(f, _)| f.as_str()),
+            &analysis.derives,
+        )?;
+        if analysis.derives.is_empty() {
+            generate_debug(w, analysis)?;
+        }
+    } else if let (Some(copy_fn), Some(free_fn)) = (
+        analysis.specials.traits().get(&Type::Copy),
+        analysis.specials.traits().get(&Type::Free),
+    ) {
+        general::define_boxed_type(
+            w,
+            env,
+            &analysis.name,
+            c_type,
+            &copy_fn.glib_name,
+            &free_fn.glib_name,
+            &None,
+            &None,
+            glib_get_type,
+            &analysis.derives,
+        )?;
+    } else if let Some((ref glib_get_type, _)) = analysis.glib_get_type {
+        general::define_auto_boxed_type(
+            w,
+            env,
+            &analysis.name,
+            c_type,
+            &None,
+            &None,
+            glib_get_type,
+            &analysis.derives,
+        )?;
+    } else {
+        // This is checked in analysis::union already
+        unreachable!(
+            "Missing memory management functions for {}",
+            analysis.full_name
+        );
+    }
+
+    record::generate_fields(
+        w,
+        env,
+        &analysis.name,
+        c_type,
+        &analysis.fields,
+        analysis.is_direct,
+        Some(analysis.tag.map(|pos| &analysis.fields[pos])),
+    )?;
+
+    if analysis
+        .functions
+        .iter()
+        .any(|f| f.status.need_generate() && !f.visibility.hidden())
+    {
+        writeln!(w)?;
+        write!(w, "impl {} {{", analysis.name)?;
+
+        for func_analysis in &analysis.functions {
+            function::generate(
+                w,
+                env,
+                func_analysis,
+                Some(&analysis.specials),
+                false,
+                false,
+                1,
+            )?;
+        }
+
+        writeln!(w, "}}")?;
+    }
+
+    trait_impls::generate(
+        w,
+        env,
+        &analysis.name,
+        &analysis.functions,
+        &analysis.specials,
+        None,
+    )?;
+
+    if analysis.concurrency != library::Concurrency::None {
+        writeln!(w)?;
+    }
+
+    match analysis.concurrency {
+        library::Concurrency::Send | library::Concurrency::SendSync => {
+            writeln!(w, "unsafe impl Send for {} {{}}", analysis.name)?;
+        }
+        library::Concurrency::SendUnique => {
+            panic!("SendUnique concurrency can only be autogenerated for GObject subclasses");
+        }
+        _ => (),
+    }
+
+    if analysis.concurrency == library::Concurrency::SendSync {
+        writeln!(w, "unsafe impl Sync for {} {{}}", analysis.name)?;
+    }
+
+    Ok(())
+}
+
+/// Implements `Debug` showing the tag, the only field that is always safe to read.
+fn generate_debug(w: &mut dyn Write, analysis: &analysis::union::Info) -> Result<()> {
+    writeln!(w)?;
+    writeln!(w, "impl std::fmt::Debug for {} {{", analysis.name)?;
+    writeln!(
+        w,
+        "\tfn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {{"
+    )?;
+    writeln!(w, "\t\tf.debug_struct(\"{}\")", analysis.name)?;
+    if let Some(tag) = analysis.tag.map(|pos| &analysis.fields[pos]) {
+        writeln!(
+            w,
+            "\t\t\t.field(\"{}\", &self.{}())",
+            tag.name,
+            tag.getter.as_ref().unwrap()
+        )?;
+    }
+    writeln!(w, "\t\t\t.finish()")?;
+    writeln!(w, "\t}}")?;
+    writeln!(w, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::env::tests::env;
+
+    const UNIONS: &str = r#"
+    <enumeration name="EventType" c:type="TestEventType">
+      <member name="key" value="0" c:identifier="TEST_EVENT_KEY" glib:nick="key"/>
+      <member name="button" value="1" c:identifier="TEST_EVENT_BUTTON" glib:nick="button"/>
+    </enumeration>
+    <union name="Value" c:type="TestValue" glib:type-name="TestValue" glib:get-type="test_value_get_type">
+      <field name="int_value" writable="1"><type name="gint" c:type="gint"/></field>
+      <field name="double_value" writable="1"><type name="gdouble" c:type="gdouble"/></field>
+    </union>
+    <union name="Event" c:type="TestEvent">
+      <field name="type" writable="1"><type name="EventType" c:type="TestEventType"/></field>
+      <field name="button" writable="1"><type name="guint" c:type="guint"/></field>
+      <field name="keyval" writable="1"><type name="guint" c:type="guint"/></field>
+    </union>
+"#;
+
+    const CONFIG: &str = r#"
+[[object]]
+name = "Test.EventType"
+status = "generate"
+
+[[object]]
+name = "Test.Value"
+status = "generate"
+generate_fields = true
+
+[[object]]
+name = "Test.Event"
+status = "generate"
+direct = true
+tag = "type"
+generate_fields = true
+    [[object.field]]
+    name = "button"
+    tag_values = ["button"]
+    [[object.field]]
+    name = "keyval"
+    tag_values = ["key"]
+"#;
+
+    #[test]
+    fn unions_are_boxed_unless_direct() {
+        let t = env(UNIONS, CONFIG);
+        let code = t.generate("value.rs");
+        assert!(code.contains("pub struct Value(Boxed<ffi::TestValue>);"));
+        assert!(code.contains("get_type => || ffi::test_value_get_type(),"));
+        assert!(!code.contains("#[repr(transparent)]"));
+        // Any variant can be read when there is no tag
+        assert!(code.contains("pub unsafe fn int_value(&self) -> i32 {"));
+        assert!(code.contains("pub fn set_int_value(&mut self, int_value: i32) {"));
+    }
+
+    #[test]
+    fn direct_unions_are_configured() {
+        let t = env(UNIONS, CONFIG);
+        let code = t.generate("event.rs");
+        assert!(code.contains("#[repr(transparent)]\n#[doc(alias = \"TestEvent\")]\npub struct Event(ffi::TestEvent);"));
+        assert!(code.contains("impl FromGlib<ffi::TestEvent> for Event {"));
+        assert!(!code.contains("Boxed<"));
+    }
+
+    #[test]
+    fn tagged_union_getters() {
+        let t = env(UNIONS, CONFIG);
+        let code = t.generate("event.rs");
+        assert!(code.contains(
+            "pub fn type_(&self) -> EventType {\n        unsafe { from_glib(self.0.type_) }"
+        ));
+        assert!(code.contains("pub fn button(&self) -> Option<u32> {\n        if matches!(self.type_(), EventType::Button) {\n            Some(unsafe { self.0.button })"));
+        assert!(code.contains("if matches!(self.type_(), EventType::Key) {"));
+        // Writing a variant could make it inconsistent with the tag
+        assert!(!code.contains("fn set_"));
+        assert!(code.contains(".field(\"type_\", &self.type_())"));
+    }
+}
@@ -0,0 +1,28 @@
+use crate::{env::Env, file_saver::*, nameutil::*};
+use log::info;
+use std::path::Path;
+
+pub fn generate(env: &Env, root_path: &Path, mod_rs: &mut Vec<String>) {
+    info!("Generate unions");
+    for union_analysis in env.analysis.unions.values() {
+        let obj = &env.config.objects[&union_analysis.full_name];
+        if !obj.status.need_generate() {
+            continue;
+        }
+
+        let mod_name = obj
+            .module_name
+            .clone()
+            .unwrap_or_else(|| module_name(split_namespace_name(&union_analysis.full_name).1));
+
+        let mut path = root_path.join(&mod_name);
+        path.set_extension("rs");
+        info!("Generating file {:?}", path);
+
+        save_to_file(path, env.config.make_backup, |w| {
+            super::union::generate(w, env, union_analysis)
+        });
+
+        super::record::generate_reexports(env, union_analysis, &mod_name, mod_rs);
+    }
+}
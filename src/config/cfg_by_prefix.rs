@@ -0,0 +1,118 @@
+use super::error::TomlHelper;
+
+/// Cfg condition applied to every C symbol starting with `prefix`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CfgByPrefix {
+    pub prefix: String,
+    pub cfg_condition: String,
+}
+
+pub fn read_cfg_by_prefix(toml: &toml::Value) -> Result<Vec<CfgByPrefix>, String> {
+    let v = match toml.lookup("cfg_by_prefix") {
+        Some(a) => a.as_result_vec("cfg_by_prefix")?,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut res = Vec::with_capacity(v.len());
+    for o in v {
+        o.check_unwanted(&["prefix", "cfg_condition"], "cfg_by_prefix");
+        let cfg_condition = o
+            .lookup_str("cfg_condition", "No cfg_condition in cfg_by_prefix")?
+            .to_owned();
+        let prefix = o.lookup("prefix").ok_or("No prefix in cfg_by_prefix")?;
+        let prefixes = match prefix.as_array() {
+            Some(a) => a
+                .iter()
+                .map(|v| {
+                    v.as_str().map(str::to_owned).ok_or_else(|| {
+                        "cfg_by_prefix.prefix expected to be string or array of string".to_owned()
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => vec![prefix.as_result_str("cfg_by_prefix.prefix")?.to_owned()],
+        };
+        for prefix in prefixes {
+            res.push(CfgByPrefix {
+                prefix,
+                cfg_condition: cfg_condition.clone(),
+            });
+        }
+    }
+
+    Ok(res)
+}
+
+/// Returns the cfg condition of the longest prefix matching `c_name`.
+pub fn find_cfg_by_prefix<'a>(list: &'a [CfgByPrefix], c_name: &str) -> Option<&'a str> {
+    list.iter()
+        .filter(|c| c_name.starts_with(&c.prefix))
+        .max_by_key(|c| c.prefix.len())
+        .map(|c| c.cfg_condition.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toml(input: &str) -> ::toml::Value {
+        let value = ::toml::from_str(input);
+        assert!(value.is_ok());
+        value.unwrap()
+    }
+
+    #[test]
+    fn test_read_cfg_by_prefix() {
+        let toml = toml(
+            r#"
+[[cfg_by_prefix]]
+prefix = ["GUnix", "g_unix_"]
+cfg_condition = "unix"
+
+[[cfg_by_prefix]]
+prefix = "GWin32"
+cfg_condition = "windows"
+"#,
+        );
+        let list = read_cfg_by_prefix(&toml).unwrap();
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(
+            list[1],
+            CfgByPrefix {
+                prefix: "g_unix_".to_owned(),
+                cfg_condition: "unix".to_owned(),
+            }
+        );
+        assert_eq!(
+            list[2],
+            CfgByPrefix {
+                prefix: "GWin32".to_owned(),
+                cfg_condition: "windows".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_find_cfg_by_prefix() {
+        let list = vec![
+            CfgByPrefix {
+                prefix: "g_unix_".to_owned(),
+                cfg_condition: "unix".to_owned(),
+            },
+            CfgByPrefix {
+                prefix: "g_unix_fd_".to_owned(),
+                cfg_condition: "all(unix, feature = \"fd\")".to_owned(),
+            },
+        ];
+
+        assert_eq!(
+            find_cfg_by_prefix(&list, "g_unix_input_stream_new"),
+            Some("unix")
+        );
+        assert_eq!(
+            find_cfg_by_prefix(&list, "g_unix_fd_list_new"),
+            Some("all(unix, feature = \"fd\")")
+        );
+        assert_eq!(find_cfg_by_prefix(&list, "g_file_new_for_path"), None);
+    }
+}
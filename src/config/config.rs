@@ -1,5 +1,7 @@
 use super::{
+    cfg_by_prefix::{find_cfg_by_prefix, read_cfg_by_prefix, CfgByPrefix},
     external_libraries::{read_external_libraries, ExternalLibrary},
+    functions_modules::{find_functions_module, read_functions_modules, FunctionsModule},
     gobjects, WorkMode,
 };
 use crate::{
@@ -34,6 +36,7 @@ pub struct Config {
     pub make_backup: bool,
     pub generate_safety_asserts: bool,
     pub deprecate_by_min_version: bool,
+    pub deprecated_feature: bool,
     pub show_statistics: bool,
     pub concurrency: library::Concurrency,
     pub single_version_file: Option<PathBuf>,
@@ -42,9 +45,14 @@ pub struct Config {
     pub docs_rs_features: Vec<String>,
     pub disable_format: bool,
     pub split_build_rs: bool,
+    pub serde: bool,
+    pub concrete_cancellable: bool,
+    pub impl_trait_args: bool,
     pub extra_versions: Vec<Version>,
     pub lib_version_overrides: HashMap<Version, Version>,
     pub feature_dependencies: HashMap<Version, Vec<String>>,
+    pub cfg_by_prefix: Vec<CfgByPrefix>,
+    pub functions_modules: Vec<FunctionsModule>,
 }
 
 impl Config {
@@ -217,6 +225,11 @@ impl Config {
             None => false,
         };
 
+        let deprecated_feature = match toml.lookup("options.deprecated_feature") {
+            Some(v) => v.as_result_bool("options.deprecated_feature")?,
+            None => false,
+        };
+
         let single_version_file = match toml.lookup("options.single_version_file") {
             Some(v) => match v.as_result_bool("options.single_version_file") {
                 Ok(false) => None,
@@ -243,9 +256,26 @@ impl Config {
             None => false,
         };
 
+        let serde = match toml.lookup("options.serde") {
+            Some(v) => v.as_result_bool("options.serde")?,
+            None => false,
+        };
+
+        let concrete_cancellable = match toml.lookup("options.concrete_cancellable") {
+            Some(v) => v.as_result_bool("options.concrete_cancellable")?,
+            None => false,
+        };
+
+        let impl_trait_args = match toml.lookup("options.impl_trait_args") {
+            Some(v) => v.as_result_bool("options.impl_trait_args")?,
+            None => false,
+        };
+
         let extra_versions = read_extra_versions(&toml)?;
         let lib_version_overrides = read_lib_version_overrides(&toml)?;
         let feature_dependencies = read_feature_dependencies(&toml)?;
+        let cfg_by_prefix = read_cfg_by_prefix(&toml)?;
+        let functions_modules = read_functions_modules(&toml)?;
 
         Ok(Config {
             work_mode,
@@ -262,6 +292,7 @@ impl Config {
             make_backup,
             generate_safety_asserts,
             deprecate_by_min_version,
+            deprecated_feature,
             show_statistics,
             concurrency,
             single_version_file,
@@ -270,12 +301,34 @@ impl Config {
             docs_rs_features,
             disable_format,
             split_build_rs,
+            serde,
+            concrete_cancellable,
+            impl_trait_args,
             extra_versions,
             lib_version_overrides,
             feature_dependencies,
+            cfg_by_prefix,
+            functions_modules,
         })
     }
 
+    /// Whether serde implementations are generated for the object, behind the `serde` feature
+    pub fn generate_serde(&self, full_name: &str) -> bool {
+        self.objects
+            .get(full_name)
+            .and_then(|obj| obj.serde)
+            .unwrap_or(self.serde)
+    }
+
+    /// Whether `Gio.Cancellable` parameters of the object's functions are taken as
+    /// `Option<&Cancellable>` instead of a generic `IsA<Cancellable>` parameter
+    pub fn concrete_cancellable(&self, full_name: &str) -> bool {
+        self.objects
+            .get(full_name)
+            .and_then(|obj| obj.concrete_cancellable)
+            .unwrap_or(self.concrete_cancellable)
+    }
+
     pub fn library_full_name(&self) -> String {
         format!("{}-{}", self.library_name, self.library_version)
     }
@@ -290,6 +343,16 @@ impl Config {
         })
     }
 
+    /// Returns the cfg condition configured by `[[cfg_by_prefix]]` for a C symbol.
+    pub fn cfg_condition_by_prefix(&self, c_name: &str) -> Option<String> {
+        find_cfg_by_prefix(&self.cfg_by_prefix, c_name).map(ToOwned::to_owned)
+    }
+
+    /// Returns the module configured by `[[functions_module]]` for a global function.
+    pub fn functions_module(&self, name: &str) -> Option<&str> {
+        find_functions_module(&self.functions_modules, name)
+    }
+
     pub fn resolve_type_ids(&mut self, library: &Library) {
         gobjects::resolve_type_ids(&mut self.objects, library)
     }
@@ -443,4 +506,32 @@ mod tests {
             PathBuf::from("/tmp/glib/_vers.dat")
         );
     }
+
+    const CONFIG: &str = r#"
+[[object]]
+name = "Test.Widget"
+status = "generate"
+
+[[object]]
+name = "Test.Button"
+status = "generate"
+concrete_cancellable = false
+"#;
+
+    #[test]
+    fn concrete_cancellable() {
+        let t = crate::env::tests::env("", CONFIG);
+        assert!(!t.env.config.concrete_cancellable("Test.Widget"));
+
+        let t = crate::env::tests::env("", &format!("concrete_cancellable = true\n{}", CONFIG));
+        assert!(t.env.config.concrete_cancellable("Test.Widget"));
+        assert!(t.env.config.concrete_cancellable("Test.Other"));
+        assert!(!t.env.config.concrete_cancellable("Test.Button"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid `options.concrete_cancellable` value")]
+    fn concrete_cancellable_not_bool() {
+        crate::env::tests::env("", &format!("concrete_cancellable = 1\n{}", CONFIG));
+    }
 }
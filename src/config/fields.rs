@@ -0,0 +1,156 @@
+use super::{error::TomlHelper, gobjects::GStatus, ident::Ident, parsable::Parse};
+use crate::version::Version;
+use log::error;
+use toml::Value;
+
+#[derive(Clone, Debug)]
+pub struct Field {
+    pub ident: Ident,
+    pub status: GStatus,
+    pub version: Option<Version>,
+    pub rename: Option<String>,
+    pub tag_values: Vec<String>,
+}
+
+impl Parse for Field {
+    fn parse(toml: &Value, object_name: &str) -> Option<Field> {
+        let ident = match Ident::parse(toml, object_name, "field") {
+            Some(ident) => ident,
+            None => {
+                error!(
+                    "No 'name' or 'pattern' given for field for object {}",
+                    object_name
+                );
+                return None;
+            }
+        };
+
+        toml.check_unwanted(
+            &[
+                "name",
+                "pattern",
+                "ignore",
+                "manual",
+                "version",
+                "rename",
+                "tag_values",
+            ],
+            &format!("field {}", object_name),
+        );
+
+        let status = {
+            if toml
+                .lookup("ignore")
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+            {
+                GStatus::Ignore
+            } else if toml
+                .lookup("manual")
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+            {
+                GStatus::Manual
+            } else {
+                GStatus::Generate
+            }
+        };
+        let version = toml
+            .lookup("version")
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse().ok());
+        let rename = toml
+            .lookup("rename")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        let tag_values = toml
+            .lookup("tag_values")
+            .and_then(Value::as_array)
+            .map(|a| {
+                a.iter()
+                    .filter_map(Value::as_str)
+                    .map(ToOwned::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Field {
+            ident,
+            status,
+            version,
+            rename,
+            tag_values,
+        })
+    }
+}
+
+impl AsRef<Ident> for Field {
+    fn as_ref(&self) -> &Ident {
+        &self.ident
+    }
+}
+
+pub type Fields = Vec<Field>;
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{ident::Ident, parsable::Parse},
+        *,
+    };
+
+    fn toml(input: &str) -> ::toml::Value {
+        let value = ::toml::from_str(input);
+        assert!(value.is_ok());
+        value.unwrap()
+    }
+
+    #[test]
+    fn field_parse_default() {
+        let toml = toml(
+            r#"
+name = "width"
+"#,
+        );
+        let f = Field::parse(&toml, "a").unwrap();
+        assert_eq!(f.ident, Ident::Name("width".into()));
+        assert!(f.status.need_generate());
+        assert_eq!(f.rename, None);
+    }
+
+    #[test]
+    fn field_parse_rename() {
+        let toml = toml(
+            r#"
+name = "type"
+rename = "kind"
+"#,
+        );
+        let f = Field::parse(&toml, "a").unwrap();
+        assert_eq!(f.rename, Some("kind".into()));
+    }
+
+    #[test]
+    fn field_parse_tag_values() {
+        let toml = toml(
+            r#"
+name = "button"
+tag_values = ["button_press", "button_release"]
+"#,
+        );
+        let f = Field::parse(&toml, "a").unwrap();
+        assert_eq!(f.tag_values, vec!["button_press", "button_release"]);
+    }
+
+    #[test]
+    fn field_parse_manual() {
+        let toml = toml(
+            r#"
+pattern = "priv_.*"
+manual = true
+"#,
+        );
+        let f = Field::parse(&toml, "a").unwrap();
+        assert!(f.status.manual());
+    }
+}
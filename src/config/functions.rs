@@ -7,7 +7,9 @@ use super::{
     string_type::StringType,
 };
 use crate::{
-    analysis::safety_assertion_mode::SafetyAssertionMode, library::Nullable, version::Version,
+    analysis::{safety_assertion_mode::SafetyAssertionMode, special_functions},
+    library::Nullable,
+    version::Version,
 };
 use log::error;
 use std::str::FromStr;
@@ -22,6 +24,8 @@ pub struct Parameter {
     pub nullable: Option<Nullable>,
     pub length_of: Option<String>,
     pub string_type: Option<StringType>,
+    /// Callback type giving the signature of a `GClosure` parameter
+    pub closure_signature: Option<String>,
 }
 
 impl Parse for Parameter {
@@ -44,6 +48,7 @@ impl Parse for Parameter {
                 "name",
                 "pattern",
                 "string_type",
+                "closure_signature",
             ],
             &format!("function parameter {}", object_name),
         );
@@ -76,12 +81,18 @@ impl Parse for Parameter {
             },
         };
 
+        let closure_signature = toml
+            .lookup("closure_signature")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
         Some(Parameter {
             ident,
             constant,
             nullable,
             length_of,
             string_type,
+            closure_signature,
         })
     }
 }
@@ -94,11 +105,73 @@ impl AsRef<Ident> for Parameter {
 
 pub type Parameters = Vec<Parameter>;
 
+/// Integer value returned by a function on failure, with the error message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntReturnIsError {
+    pub value: i64,
+    pub message: String,
+}
+
+impl IntReturnIsError {
+    fn parse(toml: &Value, object_name: &str) -> Option<IntReturnIsError> {
+        let value = toml.lookup("value").and_then(Value::as_integer);
+        let message = toml.lookup("message").and_then(Value::as_str);
+        match (value, message) {
+            (Some(value), Some(message)) => Some(IntReturnIsError {
+                value,
+                message: message.to_owned(),
+            }),
+            _ => {
+                error!(
+                    "int_return_is_error needs a value and a message for object {}",
+                    object_name
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Stream of the results of an async function called repeatedly, until it returns `None`
+/// or, with `end = "empty"`, an empty result.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stream {
+    /// Type of the items, by default the result of the function
+    pub item: Option<String>,
+    /// Whether an empty result ends the stream
+    pub end_on_empty: bool,
+}
+
+impl Stream {
+    fn parse(toml: &Value, object_name: &str) -> Option<Stream> {
+        toml.check_unwanted(&["item", "end"], &format!("stream {}", object_name));
+        let item = toml
+            .lookup("item")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        let end_on_empty = match toml.lookup("end") {
+            None => false,
+            Some(end) if end.as_str() == Some("empty") => true,
+            Some(end) => {
+                error!(
+                    "Unknown stream end {} for object {}, only \"empty\" is supported",
+                    end, object_name
+                );
+                return None;
+            }
+        };
+        Some(Stream { item, end_on_empty })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Return {
     pub nullable: Option<Nullable>,
     pub bool_return_is_error: Option<String>,
     pub nullable_return_is_error: Option<String>,
+    pub int_return_is_error: Option<IntReturnIsError>,
+    pub int_return_is_none: Option<i64>,
+    pub bool_return_is_none: Option<bool>,
     pub use_return_for_result: Option<bool>,
     pub string_type: Option<StringType>,
     pub type_name: Option<String>,
@@ -111,6 +184,9 @@ impl Return {
                 nullable: None,
                 bool_return_is_error: None,
                 nullable_return_is_error: None,
+                int_return_is_error: None,
+                int_return_is_none: None,
+                bool_return_is_none: None,
                 use_return_for_result: None,
                 string_type: None,
                 type_name: None,
@@ -123,6 +199,9 @@ impl Return {
                 "nullable",
                 "bool_return_is_error",
                 "nullable_return_is_error",
+                "int_return_is_error",
+                "int_return_is_none",
+                "bool_return_is_none",
                 "use_return_for_result",
                 "string_type",
                 "type",
@@ -139,6 +218,11 @@ impl Return {
             .lookup("nullable_return_is_error")
             .and_then(Value::as_str)
             .map(ToOwned::to_owned);
+        let int_return_is_error = v
+            .lookup("int_return_is_error")
+            .and_then(|v| IntReturnIsError::parse(v, object_name));
+        let int_return_is_none = v.lookup("int_return_is_none").and_then(Value::as_integer);
+        let bool_return_is_none = v.lookup("bool_return_is_none").and_then(Value::as_bool);
         let use_return_for_result = v.lookup("use_return_for_result").and_then(Value::as_bool);
         let string_type = v.lookup("string_type").and_then(Value::as_str);
         let string_type = match string_type {
@@ -167,6 +251,9 @@ impl Return {
             nullable,
             bool_return_is_error,
             nullable_return_is_error,
+            int_return_is_error,
+            int_return_is_none,
+            bool_return_is_none,
             use_return_for_result,
             string_type,
             type_name,
@@ -204,6 +291,12 @@ pub struct Function {
     pub no_future: bool,
     pub rename: Option<String>,
     pub assertion: Option<SafetyAssertionMode>,
+    pub move_to: Option<String>,
+    pub caller_allocated_buffers: bool,
+    pub std_trait: Option<special_functions::Type>,
+    pub error_domain: Option<String>,
+    pub varargs_twin: Option<String>,
+    pub stream: Option<Stream>,
 }
 
 impl Parse for Function {
@@ -235,6 +328,12 @@ impl Parse for Function {
                 "no_future",
                 "rename",
                 "assertion",
+                "move_to",
+                "caller_allocated_buffers",
+                "trait",
+                "error_domain",
+                "varargs_twin",
+                "stream",
             ],
             &format!("function {}", object_name),
         );
@@ -303,6 +402,32 @@ impl Parse for Function {
             error!("{}", err);
         }
         let assertion = assertion.ok().flatten();
+        let move_to = toml
+            .lookup("move_to")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        let caller_allocated_buffers = toml
+            .lookup("caller_allocated_buffers")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let std_trait = toml.lookup("trait").and_then(Value::as_str).and_then(|s| {
+            let std_trait = special_functions::Type::from_trait_name(s);
+            if std_trait.is_none() {
+                error!("Unsupported trait {} for object {}", s, object_name);
+            }
+            std_trait
+        });
+        let error_domain = toml
+            .lookup("error_domain")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        let varargs_twin = toml
+            .lookup("varargs_twin")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+        let stream = toml
+            .lookup("stream")
+            .and_then(|v| Stream::parse(v, object_name));
 
         Some(Function {
             ident,
@@ -318,6 +443,12 @@ impl Parse for Function {
             no_future,
             rename,
             assertion,
+            move_to,
+            caller_allocated_buffers,
+            std_trait,
+            error_domain,
+            varargs_twin,
+            stream,
         })
     }
 }
@@ -435,6 +566,72 @@ cfg_condition = 'unix'
         assert_eq!(f.cfg_condition, Some("unix".to_string()));
     }
 
+    #[test]
+    fn function_parse_move_to() {
+        let toml = toml(
+            r#"
+name = "func1"
+move_to = "Gdk.Window"
+"#,
+        );
+        let f = Function::parse(&toml, "a").unwrap();
+        assert_eq!(f.move_to, Some("Gdk.Window".to_string()));
+    }
+
+    #[test]
+    fn function_parse_caller_allocated_buffers() {
+        let toml = toml(
+            r#"
+name = "read"
+caller_allocated_buffers = true
+"#,
+        );
+        let f = Function::parse(&toml, "a").unwrap();
+        assert!(f.caller_allocated_buffers);
+    }
+
+    #[test]
+    fn function_parse_std_trait() {
+        let config = toml(
+            r#"
+name = "parse"
+trait = "FromStr"
+"#,
+        );
+        let f = Function::parse(&config, "a").unwrap();
+        assert_eq!(f.std_trait, Some(special_functions::Type::FromStr));
+
+        let config = toml(
+            r#"
+name = "get_name"
+trait = "AsRef<str>"
+"#,
+        );
+        let f = Function::parse(&config, "a").unwrap();
+        assert_eq!(f.std_trait, Some(special_functions::Type::AsRefStr));
+
+        let config = toml(
+            r#"
+name = "clone"
+trait = "Clone"
+"#,
+        );
+        let f = Function::parse(&config, "a").unwrap();
+        assert_eq!(f.std_trait, None);
+    }
+
+    #[test]
+    fn function_parse_error_domain() {
+        let config = toml(
+            r#"
+name = "load"
+error_domain = "Gio.IOErrorEnum"
+"#,
+        );
+        let f = Function::parse(&config, "a").unwrap();
+        assert_eq!(f.error_domain, Some("Gio.IOErrorEnum".to_owned()));
+    }
+
     #[test]
     fn function_parse_return_nullable_default1() {
         let toml = toml(
@@ -521,6 +718,40 @@ nullable = true
         assert_eq!(f.ret.nullable, Some(Nullable(true)));
     }
 
+    #[test]
+    fn function_parse_return_int_sentinels() {
+        let toml = toml(
+            r#"
+name = "func1"
+[return]
+int_return_is_error = { value = -1, message = "Failed to open" }
+int_return_is_none = 0
+"#,
+        );
+        let f = Function::parse(&toml, "a").unwrap();
+        assert_eq!(
+            f.ret.int_return_is_error,
+            Some(IntReturnIsError {
+                value: -1,
+                message: "Failed to open".to_owned(),
+            })
+        );
+        assert_eq!(f.ret.int_return_is_none, Some(0));
+    }
+
+    #[test]
+    fn function_parse_return_bool_return_is_none() {
+        let toml = toml(
+            r#"
+name = "get_bounds"
+[return]
+bool_return_is_none = true
+"#,
+        );
+        let f = Function::parse(&toml, "a").unwrap();
+        assert_eq!(f.ret.bool_return_is_none, Some(true));
+    }
+
     #[test]
     fn functions_parse_empty_for_none() {
         let fns = Functions::parse(None, "a");
@@ -632,4 +863,46 @@ rename = "anoth er"
         let f = Function::parse(&toml, "a");
         assert!(f.is_none());
     }
+
+    #[test]
+    fn functions_parse_stream() {
+        let f = toml(
+            r#"
+name = "next_files_async"
+stream = { item = "Vec<FileInfo>", end = "empty" }
+"#,
+        );
+        let f = Function::parse(&f, "a").unwrap();
+        assert_eq!(
+            f.stream,
+            Some(Stream {
+                item: Some("Vec<FileInfo>".to_owned()),
+                end_on_empty: true,
+            })
+        );
+
+        let f = toml(
+            r#"
+name = "read_line_async"
+stream = {}
+"#,
+        );
+        let f = Function::parse(&f, "a").unwrap();
+        assert_eq!(
+            f.stream,
+            Some(Stream {
+                item: None,
+                end_on_empty: false,
+            })
+        );
+
+        let f = toml(
+            r#"
+name = "next_files_async"
+stream = { end = "never" }
+"#,
+        );
+        let f = Function::parse(&f, "a").unwrap();
+        assert_eq!(f.stream, None);
+    }
 }
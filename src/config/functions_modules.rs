@@ -0,0 +1,143 @@
+use super::error::TomlHelper;
+use crate::nameutil;
+use regex::Regex;
+
+/// Files always generated next to the functions modules.
+const RESERVED_MODULES: &[&str] = &[
+    "alias",
+    "constants",
+    "enums",
+    "flags",
+    "functions",
+    "mod",
+    "traits",
+    "typed_error",
+];
+
+/// Module the global functions with a name matching `pattern` are generated in.
+#[derive(Clone, Debug)]
+pub struct FunctionsModule {
+    pub name: String,
+    pub pattern: Regex,
+}
+
+pub fn read_functions_modules(toml: &toml::Value) -> Result<Vec<FunctionsModule>, String> {
+    let v = match toml.lookup("functions_module") {
+        Some(a) => a.as_result_vec("functions_module")?,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut res = Vec::with_capacity(v.len());
+    for o in v {
+        o.check_unwanted(&["name", "pattern"], "functions_module");
+        let name = o
+            .lookup_str("name", "No name in functions_module")?
+            .to_owned();
+        check_module_name(&name)?;
+        let pattern = o.lookup_str("pattern", "No pattern in functions_module")?;
+        let pattern = Regex::new(pattern)
+            .map_err(|e| format!("Bad pattern in functions_module {}: {}", name, e))?;
+        res.push(FunctionsModule { name, pattern });
+    }
+
+    Ok(res)
+}
+
+fn check_module_name(name: &str) -> Result<(), String> {
+    let is_identifier = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && name != "_";
+    if !is_identifier {
+        Err(format!(
+            "functions_module name `{}` isn't a lowercase identifier",
+            name
+        ))
+    } else if nameutil::needs_mangling(name) {
+        Err(format!(
+            "functions_module name `{}` is a Rust keyword",
+            name
+        ))
+    } else if RESERVED_MODULES.contains(&name) {
+        Err(format!(
+            "functions_module can't be named `{}`, it's a generated module",
+            name
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the module of the first `[[functions_module]]` matching the function `name`.
+pub fn find_functions_module<'a>(list: &'a [FunctionsModule], name: &str) -> Option<&'a str> {
+    list.iter()
+        .find(|m| m.pattern.is_match(name))
+        .map(|m| m.name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toml(input: &str) -> ::toml::Value {
+        let value = ::toml::from_str(input);
+        assert!(value.is_ok());
+        value.unwrap()
+    }
+
+    #[test]
+    fn test_read_functions_modules() {
+        let toml = toml(
+            r#"
+[[functions_module]]
+name = "uri"
+pattern = "^uri_"
+
+[[functions_module]]
+name = "unicode"
+pattern = "^(unichar|utf8)_"
+"#,
+        );
+        let list = read_functions_modules(&toml).unwrap();
+
+        assert_eq!(list.len(), 2);
+        assert_eq!(find_functions_module(&list, "uri_parse"), Some("uri"));
+        assert_eq!(find_functions_module(&list, "utf8_strlen"), Some("unicode"));
+        assert_eq!(find_functions_module(&list, "get_user_name"), None);
+    }
+
+    #[test]
+    fn test_read_functions_modules_reserved_name() {
+        for name in &["functions", "enums", "mod", "traits", "typed_error"] {
+            let toml = toml(&format!(
+                "[[functions_module]]\nname = \"{}\"\npattern = \"^uri_\"\n",
+                name
+            ));
+            assert!(read_functions_modules(&toml).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_read_functions_modules_invalid_name() {
+        for name in &[
+            "",
+            "_",
+            "1uri",
+            "Uri",
+            "uri-utils",
+            "uri.rs",
+            "type",
+            "fn",
+            "self",
+        ] {
+            let toml = toml(&format!(
+                "[[functions_module]]\nname = \"{}\"\npattern = \"^uri_\"\n",
+                name
+            ));
+            assert!(read_functions_modules(&toml).is_err(), "{}", name);
+        }
+        let toml = toml("[[functions_module]]\nname = \"uri_2\"\npattern = \"^uri_\"\n");
+        assert!(read_functions_modules(&toml).is_ok());
+    }
+}
@@ -2,6 +2,7 @@ use super::{
     child_properties::ChildProperties,
     constants::Constants,
     derives::Derives,
+    fields::Fields,
     functions::Functions,
     members::Members,
     properties::Properties,
@@ -11,12 +12,13 @@ use crate::{
     analysis::{conversion_type, ref_mode},
     config::{
         error::TomlHelper,
+        ident::Ident,
         parsable::{Parsable, Parse},
     },
     library::{self, Library, TypeId, MAIN_NAMESPACE},
     version::Version,
 };
-use log::warn;
+use log::{error, warn};
 use std::{collections::BTreeMap, str::FromStr};
 use toml::Value;
 
@@ -65,6 +67,7 @@ pub struct GObject {
     pub constants: Constants,
     pub signals: Signals,
     pub members: Members,
+    pub fields: Fields,
     pub properties: Properties,
     pub derives: Option<Derives>,
     pub status: GStatus,
@@ -81,6 +84,7 @@ pub struct GObject {
     pub conversion_type: Option<conversion_type::ConversionType>,
     pub use_boxed_functions: bool,
     pub generate_display_trait: bool,
+    pub generate_from_str: bool,
     pub trust_return_value_nullability: bool,
     pub manual_traits: Vec<String>,
     pub align: Option<u32>,
@@ -89,6 +93,15 @@ pub struct GObject {
     pub builder_postprocess: Option<String>,
     pub init_function_expression: Option<String>,
     pub clear_function_expression: Option<String>,
+    pub newtype: bool,
+    pub direct: Option<bool>,
+    pub generate_fields: bool,
+    pub union_tag: Option<String>,
+    pub iterator: Option<IteratorConfig>,
+    pub into_iterator: Option<String>,
+    pub serde: Option<bool>,
+    pub error_domain: Option<String>,
+    pub concrete_cancellable: Option<bool>,
 }
 
 impl Default for GObject {
@@ -99,6 +112,7 @@ impl Default for GObject {
             constants: Constants::new(),
             signals: Signals::new(),
             members: Members::new(),
+            fields: Fields::new(),
             properties: Properties::new(),
             derives: None,
             status: Default::default(),
@@ -115,6 +129,7 @@ impl Default for GObject {
             conversion_type: None,
             use_boxed_functions: false,
             generate_display_trait: true,
+            generate_from_str: true,
             trust_return_value_nullability: false,
             manual_traits: Vec::default(),
             align: None,
@@ -123,10 +138,58 @@ impl Default for GObject {
             builder_postprocess: None,
             init_function_expression: None,
             clear_function_expression: None,
+            newtype: false,
+            direct: None,
+            generate_fields: false,
+            union_tag: None,
+            iterator: None,
+            into_iterator: None,
+            serde: None,
+            error_domain: None,
+            concrete_cancellable: None,
         }
     }
 }
 
+/// `Iterator` implementation for an object with a `next`-like method
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IteratorConfig {
+    /// Name of the method returning the next item, guessed if unset
+    pub next: Option<String>,
+    /// Overrides the `Item` type of the iterator
+    pub item: Option<String>,
+}
+
+impl IteratorConfig {
+    fn parse(toml: &Value, object_name: &str) -> Option<IteratorConfig> {
+        if let Some(enabled) = toml.as_bool() {
+            return if enabled {
+                Some(IteratorConfig::default())
+            } else {
+                None
+            };
+        }
+        if toml.as_table().is_none() {
+            warn!(
+                "`iterator` of object {} must be a boolean or a table",
+                object_name
+            );
+            return None;
+        }
+        toml.check_unwanted(&["next", "item"], &format!("iterator of {}", object_name));
+        Some(IteratorConfig {
+            next: toml
+                .lookup("next")
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned),
+            item: toml
+                .lookup("item")
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned),
+        })
+    }
+}
+
 //TODO: ?change to HashMap<String, GStatus>
 pub type GObjects = BTreeMap<String, GObject>;
 
@@ -196,6 +259,7 @@ fn parse_object(
             "constant",
             "signal",
             "member",
+            "field",
             "property",
             "derive",
             "module_name",
@@ -213,6 +277,7 @@ fn parse_object(
             "must_use",
             "use_boxed_functions",
             "generate_display_trait",
+            "generate_from_str",
             "trust_return_value_nullability",
             "manual_traits",
             "align",
@@ -221,6 +286,15 @@ fn parse_object(
             "builder_postprocess",
             "init_function_expression",
             "clear_function_expression",
+            "newtype",
+            "direct",
+            "generate_fields",
+            "tag",
+            "iterator",
+            "into_iterator",
+            "serde",
+            "error_domain",
+            "concrete_cancellable",
         ],
         &format!("object {}", name),
     );
@@ -247,6 +321,7 @@ fn parse_object(
         v
     };
     let members = Members::parse(toml_object.lookup("member"), &name);
+    let fields = Fields::parse(toml_object.lookup("field"), &name);
     let properties = Properties::parse(toml_object.lookup("property"), &name);
     let derives = if let Some(derives) = toml_object.lookup("derive") {
         Some(Derives::parse(Some(derives), &name))
@@ -300,6 +375,10 @@ fn parse_object(
         .lookup("generate_display_trait")
         .and_then(Value::as_bool)
         .unwrap_or(default_generate_display_trait);
+    let generate_from_str = toml_object
+        .lookup("generate_from_str")
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
     let trust_return_value_nullability = toml_object
         .lookup("trust_return_value_nullability")
         .and_then(Value::as_bool)
@@ -347,6 +426,37 @@ fn parse_object(
         .and_then(Value::as_str)
         .map(ToOwned::to_owned);
 
+    let newtype = toml_object
+        .lookup("newtype")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let direct = toml_object.lookup("direct").and_then(Value::as_bool);
+    let generate_fields = toml_object
+        .lookup("generate_fields")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let union_tag = toml_object
+        .lookup("tag")
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned);
+    let iterator = toml_object
+        .lookup("iterator")
+        .and_then(|v| IteratorConfig::parse(v, &name));
+    let into_iterator = toml_object
+        .lookup("into_iterator")
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned);
+    let serde = toml_object.lookup("serde").and_then(Value::as_bool);
+    let error_domain = toml_object
+        .lookup("error_domain")
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned);
+    let concrete_cancellable = toml_object.lookup("concrete_cancellable").and_then(|v| {
+        v.as_result_bool("concrete_cancellable")
+            .map_err(|e| error!("{} for object {}, using the global value", e, name))
+            .ok()
+    });
+
     if (init_function_expression.is_some() && clear_function_expression.is_none())
         || (init_function_expression.is_none() && clear_function_expression.is_some())
     {
@@ -379,6 +489,7 @@ fn parse_object(
         constants,
         signals,
         members,
+        fields,
         properties,
         derives,
         status,
@@ -395,6 +506,7 @@ fn parse_object(
         conversion_type,
         use_boxed_functions,
         generate_display_trait,
+        generate_from_str,
         trust_return_value_nullability,
         manual_traits,
         align,
@@ -403,6 +515,15 @@ fn parse_object(
         init_function_expression,
         clear_function_expression,
         ignore_builder,
+        newtype,
+        direct,
+        generate_fields,
+        union_tag,
+        iterator,
+        into_iterator,
+        serde,
+        error_domain,
+        concrete_cancellable,
     }
 }
 
@@ -482,4 +603,149 @@ pub fn resolve_type_ids(objects: &mut GObjects, library: &Library) {
         }
         object.type_id = type_id;
     }
+
+    move_global_function_configs(objects, library, &global_functions_name);
+}
+
+/// Copies the configuration of global functions with `move_to` to their new owner, once for
+/// each function it matched, under the name it got there (see `Library::moved_function_name`).
+fn move_global_function_configs(objects: &mut GObjects, library: &Library, global_name: &str) {
+    let moved: Vec<_> = match objects.get(global_name) {
+        Some(obj) => obj
+            .functions
+            .iter()
+            .filter(|f| f.move_to.is_some())
+            .cloned()
+            .collect(),
+        None => return,
+    };
+
+    for func in moved {
+        let move_to = func.move_to.as_ref().unwrap();
+        let type_id = match library.find_type(MAIN_NAMESPACE, move_to) {
+            Some(type_id) => type_id,
+            None => continue,
+        };
+        let object = match objects.get_mut(move_to) {
+            Some(object) => object,
+            None => {
+                warn!(
+                    "Function `{}` moved to `{}`, which isn't configured",
+                    func.ident, move_to
+                );
+                continue;
+            }
+        };
+        for moved_func in library.type_(type_id).functions() {
+            match moved_func.moved_from {
+                Some(ref moved_from) if func.ident.is_match(moved_from) => (),
+                _ => continue,
+            }
+            let mut func = func.clone();
+            func.move_to = None;
+            func.ident = Ident::Name(moved_func.name.clone());
+            object.functions.push(func);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iterator_toml(input: &str) -> ::toml::Value {
+        let mut value: ::toml::value::Table = ::toml::from_str(input).unwrap();
+        value.remove("iterator").unwrap()
+    }
+
+    #[test]
+    fn parse_iterator_bool() {
+        let config = IteratorConfig::parse(&iterator_toml("iterator = true"), "a").unwrap();
+        assert_eq!(config, IteratorConfig::default());
+        assert_eq!(
+            IteratorConfig::parse(&iterator_toml("iterator = false"), "a"),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_iterator_table() {
+        let toml = iterator_toml(r#"iterator = { next = "next_file", item = "FileInfo" }"#);
+        assert_eq!(
+            IteratorConfig::parse(&toml, "a"),
+            Some(IteratorConfig {
+                next: Some("next_file".to_owned()),
+                item: Some("FileInfo".to_owned()),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_iterator_invalid() {
+        assert_eq!(
+            IteratorConfig::parse(&iterator_toml(r#"iterator = "next""#), "a"),
+            None
+        );
+    }
+
+    #[test]
+    fn move_function_configs() {
+        let t = crate::env::tests::env(
+            crate::library_postprocessing::tests::MOVED,
+            r#"
+[[object]]
+name = "Test.*"
+status = "generate"
+    [[object.function]]
+    pattern = "window_.*"
+    move_to = "Test.Window"
+    doc_trait_name = "WindowMovedExt"
+    [[object.function]]
+    name = "window_show"
+    move_to = "Test.Window"
+    rename = "present"
+
+[[object]]
+name = "Test.Window"
+status = "generate"
+"#,
+        );
+        let functions = &t.env.config.objects["Test.Window"].functions;
+        let configured = |name: &str| {
+            functions
+                .iter()
+                .filter(|f| f.ident.is_match(name))
+                .map(|f| (f.doc_trait_name.as_deref(), f.rename.as_deref()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            configured("show"),
+            [(Some("WindowMovedExt"), None), (None, Some("present"))]
+        );
+        assert_eq!(configured("count"), [(Some("WindowMovedExt"), None)]);
+        assert!(functions.iter().all(|f| f.move_to.is_none()));
+    }
+
+    fn object_toml(input: &str) -> GObject {
+        let toml = ::toml::from_str(&format!("name = \"Test.Widget\"\n{}", input)).unwrap();
+        parse_object(&toml, library::Concurrency::None, false, false)
+    }
+
+    #[test]
+    fn parse_concrete_cancellable() {
+        assert_eq!(object_toml("").concrete_cancellable, None);
+        assert_eq!(
+            object_toml("concrete_cancellable = true").concrete_cancellable,
+            Some(true)
+        );
+        assert_eq!(
+            object_toml("concrete_cancellable = false").concrete_cancellable,
+            Some(false)
+        );
+        // Not a boolean, the global value is used
+        assert_eq!(
+            object_toml("concrete_cancellable = \"yes\"").concrete_cancellable,
+            None
+        );
+    }
 }
@@ -1,3 +1,4 @@
+mod cfg_by_prefix;
 mod child_properties;
 #[allow(clippy::module_inception)]
 pub mod config;
@@ -5,7 +6,9 @@ pub mod constants;
 pub mod derives;
 pub mod error;
 mod external_libraries;
+pub mod fields;
 pub mod functions;
+mod functions_modules;
 pub mod gobjects;
 pub mod ident;
 pub mod matchable;
@@ -1,5 +1,5 @@
 use crate::{
-    analysis,
+    analysis::{self, conversion_type::ConversionType},
     config::{gobjects::GStatus, Config},
     library::*,
     version::Version,
@@ -52,7 +52,257 @@ impl Env {
         }
     }
 
+    /// The version from which `tid` is gated behind the `deprecated` feature, for the generated
+    /// types of the main namespace and the containers holding them.
+    pub fn type_deprecated_feature_version(&self, tid: TypeId) -> Option<Version> {
+        if !self.config.deprecated_feature {
+            return None;
+        }
+        match *self.type_(tid) {
+            Type::Array(inner)
+            | Type::CArray(inner)
+            | Type::FixedArray(inner, ..)
+            | Type::PtrArray(inner)
+            | Type::List(inner)
+            | Type::SList(inner) => self.type_deprecated_feature_version(inner),
+            Type::HashTable(key, value) => self
+                .type_deprecated_feature_version(key)
+                .into_iter()
+                .chain(self.type_deprecated_feature_version(value))
+                .min(),
+            Type::Class(_)
+            | Type::Interface(_)
+            | Type::Record(_)
+            | Type::Enumeration(_)
+            | Type::Bitfield(_)
+                if tid.ns_id == MAIN_NAMESPACE
+                    && self
+                        .type_status(&tid.full_name(&self.library))
+                        .need_generate() =>
+            {
+                self.type_(tid).get_deprecated_version()
+            }
+            _ => None,
+        }
+    }
+
+    /// The version from which an item deprecated in `deprecated` and using `types` is gated
+    /// behind the `deprecated` feature: the earliest of its own and of its gated types.
+    pub fn deprecated_feature_version(
+        &self,
+        deprecated: Option<Version>,
+        types: impl IntoIterator<Item = TypeId>,
+    ) -> Option<Version> {
+        types
+            .into_iter()
+            .filter_map(|tid| self.type_deprecated_feature_version(tid))
+            .chain(deprecated)
+            .min()
+    }
+
+    /// Whether the alias `tid` is generated as a newtype instead of a type alias. Only aliases
+    /// of types passed by value can be newtypes.
+    pub fn is_newtype_alias(&self, tid: TypeId) -> bool {
+        match *self.library.type_(tid) {
+            Type::Alias(ref alias) => {
+                self.config
+                    .objects
+                    .get(&tid.full_name(&self.library))
+                    .filter(|o| o.newtype)
+                    .is_some()
+                    && matches!(
+                        ConversionType::of(self, alias.typ),
+                        ConversionType::Direct | ConversionType::Scalar
+                    )
+            }
+            _ => false,
+        }
+    }
+
     pub fn main_sys_crate_name(&self) -> &str {
         &self.namespaces[MAIN_NAMESPACE].sys_crate_name
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        analysis, codegen,
+        config::{Config, WorkMode},
+        update_version,
+    };
+    use std::{
+        fs,
+        path::PathBuf,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    const GLIB_GIR: &str = r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0" xmlns:c="http://www.gtk.org/introspection/c/1.0" xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <package name="glib-2.0"/>
+  <namespace name="GLib" version="2.0" shared-library="libglib-2.0.so.0" c:identifier-prefixes="G" c:symbol-prefixes="g,glib">
+    <record name="Error" c:type="GError" glib:type-name="GError" glib:get-type="g_error_get_type">
+      <field name="domain" writable="1"><type name="guint32" c:type="GQuark"/></field>
+      <field name="code" writable="1"><type name="gint" c:type="gint"/></field>
+      <field name="message" writable="1"><type name="utf8" c:type="gchar*"/></field>
+    </record>
+    <record name="HashTable" c:type="GHashTable" glib:type-name="GHashTable" glib:get-type="g_hash_table_get_type"/>
+    <callback name="DestroyNotify" c:type="GDestroyNotify">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters><parameter name="data" transfer-ownership="none" nullable="1" allow-none="1"><type name="gpointer" c:type="gpointer"/></parameter></parameters>
+    </callback>
+  </namespace>
+</repository>
+"#;
+
+    const GOBJECT_GIR: &str = r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0" xmlns:c="http://www.gtk.org/introspection/c/1.0" xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <include name="GLib" version="2.0"/>
+  <package name="gobject-2.0"/>
+  <namespace name="GObject" version="2.0" shared-library="libgobject-2.0.so.0" c:identifier-prefixes="G" c:symbol-prefixes="g">
+    <class name="Object" c:symbol-prefix="object" c:type="GObject" glib:type-name="GObject" glib:get-type="g_object_get_type">
+      <field name="g_type_instance"><type name="gpointer" c:type="gpointer"/></field>
+    </class>
+    <record name="Closure" c:type="GClosure" glib:type-name="GClosure" glib:get-type="g_closure_get_type">
+      <field name="ref_count" bits="15"><type name="guint" c:type="guint"/></field>
+    </record>
+  </namespace>
+</repository>
+"#;
+
+    const GIO_GIR: &str = r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0" xmlns:c="http://www.gtk.org/introspection/c/1.0" xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <include name="GObject" version="2.0"/>
+  <package name="gio-2.0"/>
+  <namespace name="Gio" version="2.0" shared-library="libgio-2.0.so.0" c:identifier-prefixes="G" c:symbol-prefixes="g">
+    <callback name="AsyncReadyCallback" c:type="GAsyncReadyCallback">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="source_object" transfer-ownership="none" nullable="1" allow-none="1"><type name="GObject.Object" c:type="GObject*"/></parameter>
+        <parameter name="res" transfer-ownership="none"><type name="AsyncResult" c:type="GAsyncResult*"/></parameter>
+        <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1" closure="2"><type name="gpointer" c:type="gpointer"/></parameter>
+      </parameters>
+    </callback>
+    <callback name="FileProgressCallback" c:type="GFileProgressCallback">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="current_num_bytes" transfer-ownership="none"><type name="gint64" c:type="goffset"/></parameter>
+        <parameter name="total_num_bytes" transfer-ownership="none"><type name="gint64" c:type="goffset"/></parameter>
+        <parameter name="user_data" transfer-ownership="none" nullable="1" allow-none="1" closure="2"><type name="gpointer" c:type="gpointer"/></parameter>
+      </parameters>
+    </callback>
+    <interface name="AsyncResult" c:symbol-prefix="async_result" c:type="GAsyncResult" glib:type-name="GAsyncResult" glib:get-type="g_async_result_get_type">
+    </interface>
+    <class name="Cancellable" c:symbol-prefix="cancellable" c:type="GCancellable" parent="GObject.Object" glib:type-name="GCancellable" glib:get-type="g_cancellable_get_type">
+    </class>
+  </namespace>
+</repository>
+"#;
+
+    const CONFIG: &str = r#"[options]
+girs_dir = "."
+library = "Test"
+version = "1.0"
+min_cfg_version = "1.0"
+target_path = "out"
+work_mode = "normal"
+external_libraries = ["GLib", "GObject", "Gio"]
+manual = ["GLib.Error", "GObject.Closure", "GObject.Object", "Gio.AsyncResult"]
+"#;
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    /// A `Test` library analyzed in a temporary directory, removed when dropped.
+    pub struct TestEnv {
+        pub env: Env,
+        dir: PathBuf,
+    }
+
+    impl TestEnv {
+        /// Generates the crate and returns the content of a file of `src/auto`.
+        pub fn generate(&self, file_name: &str) -> String {
+            codegen::generate(&self.env);
+            let path = self.env.config.auto_path.join(file_name);
+            fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+        }
+    }
+
+    impl Drop for TestEnv {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    /// Analyzes a `Test` library made of the elements of `namespace`, along with stubs of
+    /// `GLib`, `GObject` and `Gio`. `config` is appended to the `[options]` of the
+    /// configuration, so it can start with more options before its tables.
+    pub fn env(namespace: &str, config: &str) -> TestEnv {
+        let dir = std::env::temp_dir().join(format!(
+            "gir-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let gir = format!(
+            r#"<?xml version="1.0"?>
+<repository version="1.2" xmlns="http://www.gtk.org/introspection/core/1.0" xmlns:c="http://www.gtk.org/introspection/c/1.0" xmlns:glib="http://www.gtk.org/introspection/glib/1.0">
+  <include name="GObject" version="2.0"/>
+  <include name="Gio" version="2.0"/>
+  <package name="test-1.0"/>
+  <namespace name="Test" version="1.0" shared-library="libtest.so.0" c:identifier-prefixes="Test" c:symbol-prefixes="test">
+{}
+  </namespace>
+</repository>
+"#,
+            namespace
+        );
+        for (name, content) in &[
+            ("GLib-2.0.gir", GLIB_GIR),
+            ("GObject-2.0.gir", GOBJECT_GIR),
+            ("Gio-2.0.gir", GIO_GIR),
+            ("Test-1.0.gir", &gir),
+            ("Gir.toml", &format!("{}{}", CONFIG, config)),
+        ] {
+            fs::write(dir.join(name), content).unwrap();
+        }
+
+        let config_file = dir.join("Gir.toml");
+        let mut cfg = Config::new(
+            config_file.to_str(),
+            WorkMode::Normal,
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+            false,
+            true,
+        )
+        .unwrap();
+        let mut library = Library::new(&cfg.library_name);
+        library
+            .read_file(&cfg.girs_dir, &mut vec![cfg.library_full_name()])
+            .unwrap();
+        library.preprocessing(cfg.work_mode);
+        update_version::apply_config(&mut library, &cfg);
+        library.postprocessing(&cfg);
+        cfg.resolve_type_ids(&library);
+        update_version::check_function_real_version(&mut library);
+
+        let namespaces = analysis::namespaces::run(&library);
+        let symbols = analysis::symbols::run(&library, &namespaces);
+        let class_hierarchy = analysis::class_hierarchy::run(&library);
+        let mut env = Env {
+            library,
+            config: cfg,
+            namespaces,
+            symbols: RefCell::new(symbols),
+            class_hierarchy,
+            analysis: Default::default(),
+        };
+        analysis::run(&mut env);
+        TestEnv { env, dir }
+    }
+}
@@ -82,6 +82,10 @@ pub enum ParameterScope {
     ///
     /// Can be invoked multiple times.
     Notified,
+    /// Used until the end of the program, it is never freed.
+    ///
+    /// Can be invoked multiple times.
+    Forever,
 }
 
 impl ParameterScope {
@@ -92,6 +96,10 @@ impl ParameterScope {
     pub fn is_async(self) -> bool {
         matches!(self, ParameterScope::Async)
     }
+
+    pub fn is_forever(self) -> bool {
+        matches!(self, ParameterScope::Forever)
+    }
 }
 
 impl Default for ParameterScope {
@@ -108,6 +116,7 @@ impl FromStr for ParameterScope {
             "call" => Ok(ParameterScope::Call),
             "async" => Ok(ParameterScope::Async),
             "notified" => Ok(ParameterScope::Notified),
+            "forever" => Ok(ParameterScope::Forever),
             _ => Err(format!("Unknown parameter scope type: {}", name)),
         }
     }
@@ -350,6 +359,7 @@ pub struct Member {
     pub name: String,
     pub c_identifier: String,
     pub value: String,
+    pub nick: String,
     pub doc: Option<String>,
 }
 
@@ -477,6 +487,8 @@ pub struct Function {
     pub deprecated_version: Option<Version>,
     pub doc: Option<String>,
     pub doc_deprecated: Option<String>,
+    /// Name of the global function this one was moved from with `move_to`.
+    pub moved_from: Option<String>,
 }
 
 #[derive(Debug)]
@@ -1021,6 +1033,7 @@ impl Library {
                     let depr_version = version.unwrap_or(env.config.min_cfg_version);
                     if !env.analysis.objects.contains_key(&full_name)
                         && !env.analysis.records.contains_key(&full_name)
+                        && !env.analysis.unions.contains_key(&full_name)
                         && !env.config.objects.iter().any(|o| o.1.name == full_name)
                         && depr_version >= env.config.min_cfg_version
                     {
@@ -1086,6 +1099,64 @@ impl Library {
             &self.namespace(MAIN_NAMESPACE).functions,
             "FUNCTION",
         );
+        self.show_move_candidates(env);
+        self.show_bool_return_candidates(env);
+    }
+
+    /// Lists global functions whose first parameter is a generated class or record, they
+    /// can be turned into methods with `move_to`.
+    fn show_move_candidates(&self, env: &Env) {
+        let namespace_name = &self.namespace(MAIN_NAMESPACE).name;
+        for func in &self.namespace(MAIN_NAMESPACE).functions {
+            let par = match func.parameters.first() {
+                Some(par) if par.typ.ns_id == MAIN_NAMESPACE => par,
+                _ => continue,
+            };
+            let full_name = par.typ.full_name(self);
+            if env.analysis.objects.contains_key(&full_name)
+                || env.analysis.records.contains_key(&full_name)
+                || env.analysis.unions.contains_key(&full_name)
+            {
+                println!(
+                    "[MOVE CANDIDATE] {}.{} to {}",
+                    namespace_name, func.name, full_name
+                );
+            }
+        }
+    }
+
+    /// Lists the functions returning a boolean with out parameters that are left at the default
+    /// `Option<(..)>`. It is only right when the boolean tells if the values are present, which
+    /// getters likely do, the others can keep a `(bool, ..)` tuple with `bool_return_is_none`.
+    fn show_bool_return_candidates(&self, env: &Env) {
+        use crate::analysis::out_parameters::Mode;
+
+        let infos = env
+            .analysis
+            .objects
+            .values()
+            .map(|info| &info.base)
+            .chain(env.analysis.records.values().map(|info| &info.base))
+            .chain(env.analysis.global_functions.iter())
+            .chain(env.analysis.global_functions_modules.iter());
+        for info in infos {
+            for func in &info.functions {
+                if !func.status.need_generate()
+                    || func.outs.mode != Mode::Optional
+                    || func.ret.bool_return_is_none.is_some()
+                {
+                    continue;
+                }
+                let looks_like_getter = ["get_", "lookup", "query", "find", "peek"]
+                    .iter()
+                    .any(|prefix| func.name.starts_with(prefix))
+                    || func.name.ends_with("_bounds");
+                println!(
+                    "[OPTION CANDIDATE] {}::{} with bool_return_is_none = {}",
+                    info.full_name, func.name, looks_like_getter
+                );
+            }
+        }
     }
 
     fn not_bound_functions(&self, env: &Env, prefix: &str, functions: &[Function], kind: &str) {
@@ -1114,6 +1185,7 @@ impl Library {
                     if env.type_status(&p.typ.full_name(&env.library)).ignored()
                         && !env.analysis.objects.contains_key(&full_name)
                         && !env.analysis.records.contains_key(&full_name)
+                        && !env.analysis.unions.contains_key(&full_name)
                         && !env.config.objects.iter().any(|o| o.1.name == full_name)
                     {
                         Some(full_name)
@@ -1136,6 +1208,7 @@ impl Library {
                         .ignored()
                         && !env.analysis.objects.contains_key(&full_name)
                         && !env.analysis.records.contains_key(&full_name)
+                        && !env.analysis.unions.contains_key(&full_name)
                         && !env.config.objects.iter().any(|o| o.1.name == full_name)
                     {
                         errors.push(full_name);
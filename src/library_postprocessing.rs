@@ -1,12 +1,12 @@
 use crate::{
     analysis::types::IsIncomplete,
-    config::{gobjects::GObject, Config, WorkMode},
+    config::{gobjects::GObject, matchable::Matchable, Config, WorkMode},
     library::*,
     nameutil,
     parser::is_empty_c_type,
     traits::MaybeRefAs,
 };
-use log::{error, info};
+use log::{error, info, warn};
 use std::collections::HashMap;
 
 impl Namespace {
@@ -36,6 +36,7 @@ impl Library {
         self.fix_fields();
         self.make_unrepresentable_types_opaque();
         self.mark_final_types(config);
+        self.move_global_functions(config);
         self.update_error_domain_functions(config);
     }
 
@@ -644,4 +645,191 @@ impl Library {
             }
         }
     }
+
+    fn move_global_functions(&mut self, config: &Config) {
+        // Global functions configured with `move_to` become methods (if their first
+        // parameter is an instance of the target type) or associated functions of that type.
+        let obj = match config
+            .objects
+            .get(&format!("{}.*", self.namespace(MAIN_NAMESPACE).name))
+        {
+            Some(obj) => obj,
+            None => return,
+        };
+
+        let mut moves = Vec::new();
+        for (pos, func) in self.namespace(MAIN_NAMESPACE).functions.iter().enumerate() {
+            let move_to = match obj
+                .functions
+                .matched(&func.name)
+                .iter()
+                .find_map(|f| f.move_to.as_ref())
+            {
+                Some(move_to) => move_to,
+                None => continue,
+            };
+            match self.find_type(MAIN_NAMESPACE, move_to) {
+                Some(tid)
+                    if matches!(
+                        self.type_(tid),
+                        Type::Class(_) | Type::Interface(_) | Type::Record(_)
+                    ) =>
+                {
+                    let name = self.moved_function_name(tid, &func.name);
+                    if self.type_(tid).functions().iter().any(|f| f.name == name)
+                        || moves.iter().any(|&(_, t, ref n)| t == tid && *n == name)
+                    {
+                        warn!(
+                            "Can't move function `{}`: `{}` already has a function `{}`",
+                            func.name, move_to, name
+                        );
+                    } else {
+                        moves.push((pos, tid, name))
+                    }
+                }
+                _ => error!(
+                    "Can't move function `{}`: `{}` is not a class, interface or record",
+                    func.name, move_to
+                ),
+            }
+        }
+
+        // Removed from the end to keep the positions valid, then moved in their original order
+        let mut funcs = Vec::with_capacity(moves.len());
+        for &(pos, ..) in moves.iter().rev() {
+            funcs.push(self.namespace_mut(MAIN_NAMESPACE).functions.remove(pos));
+        }
+        for ((_, tid, name), mut func) in moves.into_iter().zip(funcs.into_iter().rev()) {
+            func.moved_from = Some(std::mem::replace(&mut func.name, name));
+            match func.parameters.first_mut() {
+                Some(par) if par.typ == tid => {
+                    par.instance_parameter = true;
+                    func.kind = FunctionKind::Method;
+                }
+                _ => func.kind = FunctionKind::Function,
+            }
+            info!(
+                "Moving function `{}` to `{}`",
+                func.c_identifier.as_ref().unwrap_or(&func.name),
+                tid.full_name(self)
+            );
+            match *self.type_mut(tid) {
+                Type::Class(Class {
+                    ref mut functions, ..
+                })
+                | Type::Record(Record {
+                    ref mut functions, ..
+                })
+                | Type::Interface(Interface {
+                    ref mut functions, ..
+                }) => functions.push(func),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    /// Name of the global function `name` once moved to `tid`: the type's symbol prefix is
+    /// stripped, so `window_foo` moved to `Window` becomes `foo`.
+    pub fn moved_function_name(&self, tid: TypeId, name: &str) -> String {
+        let symbol_prefix = match *self.type_(tid) {
+            Type::Class(Class {
+                ref symbol_prefix, ..
+            })
+            | Type::Interface(Interface {
+                ref symbol_prefix, ..
+            }) => Some(symbol_prefix.as_str()),
+            Type::Record(Record {
+                ref symbol_prefix, ..
+            }) => symbol_prefix.as_deref(),
+            _ => None,
+        };
+        symbol_prefix
+            .and_then(|prefix| name.strip_prefix(prefix))
+            .and_then(|name| name.strip_prefix('_'))
+            .filter(|name| !name.is_empty())
+            .unwrap_or(name)
+            .to_owned()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{env::tests::env, library::*};
+
+    pub const MOVED: &str = r#"
+    <class name="Window" c:symbol-prefix="window" c:type="TestWindow" parent="GObject.Object" glib:type-name="TestWindow" glib:get-type="test_window_get_type">
+      <method name="hide" c:identifier="test_window_hide">
+        <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+        <parameters>
+          <instance-parameter name="window" transfer-ownership="none"><type name="Window" c:type="TestWindow*"/></instance-parameter>
+        </parameters>
+      </method>
+    </class>
+    <function name="window_show" c:identifier="test_window_show">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+      <parameters>
+        <parameter name="window" transfer-ownership="none"><type name="Window" c:type="TestWindow*"/></parameter>
+      </parameters>
+    </function>
+    <function name="window_count" c:identifier="test_window_count">
+      <return-value transfer-ownership="none"><type name="gint" c:type="gint"/></return-value>
+    </function>
+    <function name="window_hide" c:identifier="test_window_hide_all">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+    </function>
+    <function name="frobnicate" c:identifier="test_frobnicate">
+      <return-value transfer-ownership="none"><type name="none" c:type="void"/></return-value>
+    </function>
+"#;
+
+    #[test]
+    fn moved_function_name() {
+        let t = env(MOVED, "");
+        let library = &t.env.library;
+        let tid = library.find_type(MAIN_NAMESPACE, "Test.Window").unwrap();
+        assert_eq!(library.moved_function_name(tid, "window_show"), "show");
+        assert_eq!(library.moved_function_name(tid, "frobnicate"), "frobnicate");
+        assert_eq!(library.moved_function_name(tid, "windowless"), "windowless");
+        assert_eq!(library.moved_function_name(tid, "window"), "window");
+    }
+
+    #[test]
+    fn move_global_functions() {
+        let t = env(
+            MOVED,
+            r#"
+[[object]]
+name = "Test.*"
+status = "generate"
+    [[object.function]]
+    pattern = "window_.*"
+    move_to = "Test.Window"
+"#,
+        );
+        let library = &t.env.library;
+        let globals: Vec<_> = library
+            .namespace(MAIN_NAMESPACE)
+            .functions
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
+        // `Window` already has a `hide` method
+        assert_eq!(globals, ["window_hide", "frobnicate"]);
+
+        let tid = library.find_type(MAIN_NAMESPACE, "Test.Window").unwrap();
+        let moved: Vec<_> = library
+            .type_(tid)
+            .functions()
+            .iter()
+            .map(|f| (f.name.as_str(), f.moved_from.as_deref(), f.kind))
+            .collect();
+        assert_eq!(
+            moved,
+            [
+                ("hide", None, FunctionKind::Method),
+                ("show", Some("window_show"), FunctionKind::Method),
+                ("count", Some("window_count"), FunctionKind::Function),
+            ]
+        );
+    }
 }
@@ -851,6 +851,10 @@ impl Library {
         let member_name = elem.attr_required("name")?;
         let value = elem.attr_required("value")?;
         let c_identifier = elem.attr("identifier").map(|x| x.into());
+        // Same default as the nicks registered by glib-mkenums
+        let nick = elem
+            .attr("nick")
+            .map_or_else(|| member_name.replace('_', "-"), ToOwned::to_owned);
 
         let mut doc = None;
 
@@ -863,6 +867,7 @@ impl Library {
         Ok(Member {
             name: member_name.into(),
             value: value.into(),
+            nick,
             doc,
             c_identifier: c_identifier.unwrap_or_else(|| member_name.into()),
         })
@@ -941,6 +946,7 @@ impl Library {
                 deprecated_version,
                 doc,
                 doc_deprecated,
+                moved_from: None,
             })
         } else {
             Err(parser.fail_with_position(
@@ -1,6 +1,7 @@
 use super::primitives::*;
 use super::safety_assertion_mode_to_str;
 use crate::{
+    analysis::conversion_type::is_direct_fixed_array,
     chunk::{Chunk, Param, TupleMode},
     codegen::{translate_from_glib::TranslateFromGlib, translate_to_glib::TranslateToGlib},
     env::Env,
@@ -38,7 +39,9 @@ impl ToCode for Chunk {
                 vec![s]
             }
             FfiCallOutParameter { ref par } => {
-                let s = if par.caller_allocates {
+                let s = if is_direct_fixed_array(env, par.typ) {
+                    format!("{}.as_mut_ptr() as *mut _", par.name)
+                } else if par.caller_allocates {
                     format!("{}.to_glib_none_mut().0", par.name)
                 } else if par.is_uninitialized && !par.is_error {
                     format!("{}.as_mut_ptr()", par.name)
@@ -108,13 +111,22 @@ impl ToCode for Chunk {
             }
             OptionalReturn {
                 ref condition,
+                ref init,
                 ref value,
             } => {
                 let value_strings = value.to_code(env);
-                let prefix = format!("if {} {{ Some(", condition);
-                let suffix = ") } else { None }";
-                let s = format_block_one_line(&prefix, suffix, &value_strings, "", "");
-                vec![s]
+                if init.is_empty() {
+                    let prefix = format!("if {} {{ Some(", condition);
+                    let suffix = ") } else { None }";
+                    let s = format_block_one_line(&prefix, suffix, &value_strings, "", "");
+                    vec![s]
+                } else {
+                    let mut body = init.to_code(env);
+                    body.push(format_block_one_line("Some(", ")", &value_strings, "", ""));
+                    let mut v = format_block(&format!("if {} {{", condition), "} else {", &body);
+                    v.extend(format_block("", "}", &["None".to_owned()]));
+                    v
+                }
             }
             ErrorResultReturn { ref value } => {
                 let value_strings = value.to_code(env);